@@ -0,0 +1,462 @@
+use rustfinal::error::{Error, Result};
+use rustfinal::frame_codec::FrameCodec;
+use rustfinal::game_state::GameState;
+use rustfinal::hash_cash_challenge::HashCash;
+use rustfinal::challenge_registry::{generate_challenge, variant_name, ChallengeRegistry, GeneratorSettings, Registration, VARIANTS};
+use rustfinal::hash_cash_challenge::hash_algorithm::{HashAlgorithm, Md5, Sha1, Sha256};
+use rustfinal::hash_cash_challenge::seed_search::SeedRange;
+use rustfinal::protocol::{self, challenge_kinds, decode_message_of, negotiate, write_message, Challenge, ChallengeAnswer, ChallengeResult, Message, Negotiation, Subscribe, SubscribeResult, UnknownPolicy, SUPPORTED_VERSIONS, UNKNOWN_POLICY_NAMES};
+use rustfinal::solve_control::{CancellationToken, Progress, SolveLimits, Solved};
+use rustfinal::monstrous_maze_challenge::MonstrousMaze;
+use rustfinal::name_policy::{Charset, NamePolicy, CHARSET_NAMES};
+use rustfinal::recover_secret_challenge::RecoverSecret;
+use rustfinal::recover_secret_challenge::dictionary::Dictionary;
+use rustfinal::target_strategy::{strategy_named, STRATEGY_NAMES};
+
+use clap::builder::PossibleValuesParser;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::de::DeserializeOwned;
+
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::net::TcpStream;
+use std::panic::{self, AssertUnwindSafe};
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use std::{io, thread};
+use rustfinal::challenge_trait::ChallengeTrait;
+
+const DEFAULT_PORT: &str = "7878";
+/// Solve time of `play` without `--timeout`, under the 2s the reference server gives an answer
+const PLAY_TIMEOUT: Duration = Duration::from_millis(1_500);
+/// File name standing for the standard input
+const STDIN: &str = "-";
+
+fn main() -> ExitCode {
+    match run(&cli().get_matches()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn cli() -> Command<'static> {
+    Command::new("client")
+        .about("Plays the challenge game, and solves, verifies or generates its challenges")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(Command::new("play")
+            .about("Joins a game and answers every challenge it receives")
+            .arg(Arg::new("server").long("server").value_name("HOST").default_value("127.0.0.1").help("Host of the game server"))
+            .arg(Arg::new("port").long("port").value_name("PORT").value_parser(value_parser!(u16)).default_value(DEFAULT_PORT).help("Port of the game server"))
+            .arg(Arg::new("name").long("name").value_name("NAME").takes_value(true).required(true).help("Name to subscribe with"))
+            .arg(Arg::new("strategy").long("strategy").value_name("STRATEGY").value_parser(PossibleValuesParser::new(STRATEGY_NAMES)).default_value("leader")
+                .help("Player to send solved challenges to, never us nor an inactive player: the best score, the closest score above ours, the most time used or anyone"))
+            .arg(Arg::new("strict-version").long("strict-version").action(ArgAction::SetTrue)
+                .help("Refuses servers newer than this client instead of playing only the challenges it knows"))
+            .arg(Arg::new("unknown").long("unknown").value_name("POLICY").value_parser(PossibleValuesParser::new(UNKNOWN_POLICY_NAMES)).default_value("fallback")
+                .help("What to do with messages and challenges this client does not know: skip them, answer challenges with a wrong answer so that only the round is lost, or stop"))
+            .args(name_args())
+            .args(solver_args()))
+        .subcommand(Command::new("solve")
+            .about("Solves a challenge and prints the answer as JSON")
+            .arg(Arg::new("challenge").value_name("FILE").default_value(STDIN)
+                .help("Challenge as JSON, e.g. {\"MonstrousMaze\":{\"grid\":\"Y X\",\"endurance\":1}}, '-' for the standard input"))
+            .args(solver_args()))
+        .subcommand(Command::new("verify")
+            .about("Checks an answer against a challenge, exiting with 1 when it is wrong")
+            .arg(Arg::new("challenge").value_name("CHALLENGE").required(true).help("Challenge as JSON, '-' for the standard input"))
+            .arg(Arg::new("answer").value_name("ANSWER").required(true)
+                .help("Answer as JSON, e.g. {\"MonstrousMaze\":{\"path\":\">>\"}}, '-' for the standard input")))
+        .subcommand(Command::new("generate")
+            .about("Prints random challenges as JSON, one per line")
+            .arg(Arg::new("kind").long("kind").value_name("KIND").takes_value(true).value_parser(PossibleValuesParser::new(VARIANTS)).action(ArgAction::Append)
+                .help("Kind of challenge, may be repeated; every kind in turn by default"))
+            .arg(Arg::new("count").long("count").value_name("COUNT").value_parser(value_parser!(usize)).default_value("1").help("Number of challenges"))
+            .arg(Arg::new("seed").long("seed").value_name("SEED").takes_value(true).value_parser(value_parser!(u64)).help("Seed giving the same challenges on every run")))
+}
+
+/// How `play` changes a name the server refuses
+fn name_args() -> [Arg<'static>; 5] {
+    [
+        Arg::new("name-separator").long("name-separator").value_name("SEPARATOR").default_value("-")
+            .help("Put before the number added to a name already taken, as in name-2"),
+        Arg::new("name-attempts").long("name-attempts").value_name("COUNT").value_parser(value_parser!(usize)).default_value("10")
+            .help("Names tried at most before giving up"),
+        Arg::new("name-charset").long("name-charset").value_name("CHARSET").value_parser(PossibleValuesParser::new(CHARSET_NAMES)).default_value("alphanumeric")
+            .help("Characters kept when sanitizing an invalid name: ASCII letters, digits, - and _, visible ASCII, or anything but control characters"),
+        Arg::new("name-replacement").long("name-replacement").value_name("CHAR").value_parser(parse_replacement).default_value("_")
+            .help("Stands for each character removed from an invalid name, nothing when empty"),
+        Arg::new("name-max-length").long("name-max-length").value_name("LENGTH").value_parser(value_parser!(usize)).default_value("32")
+            .help("Characters kept in a sanitized name, number included"),
+    ]
+}
+
+fn parse_replacement(replacement: &str) -> std::result::Result<Option<char>, String> {
+    let mut chars = replacement.chars();
+    match (chars.next(), chars.next()) {
+        (replacement, None) => Ok(replacement),
+        _ => Err("expected a single character, or nothing".to_string()),
+    }
+}
+
+/// Options shared by the subcommands that solve challenges
+fn solver_args() -> [Arg<'static>; 5] {
+    [
+        Arg::new("language").long("language").value_name("LANGUAGE").default_value("en")
+            .help("Word list ranking RecoverSecret answers, read from dictionaries/<LANGUAGE>.txt"),
+        Arg::new("threads").long("threads").value_name("THREADS").takes_value(true).value_parser(value_parser!(usize)).help("HashCash threads, every core by default"),
+        Arg::new("seed-range").long("seed-range").value_name("RANGE").takes_value(true).value_parser(|range: &str| range.parse::<SeedRange>())
+            .help("HashCash seeds to search, as start..end, start.. or start, giving the lowest matching seed"),
+        Arg::new("share").long("share").value_name("PART/PARTS").takes_value(true).requires("seed-range").value_parser(parse_share)
+            .help("Searches only one of PARTS equal shares of the seed range"),
+        Arg::new("timeout").long("timeout").value_name("MS").takes_value(true).value_parser(value_parser!(u64))
+            .help("Time to solve a challenge before sending the best answer found so far, 1500 for play and unlimited otherwise by default"),
+    ]
+}
+
+fn parse_share(share: &str) -> std::result::Result<(u64, u64), String> {
+    let parsed = share.split_once('/').and_then(|(part, parts)| Some((part.parse::<u64>().ok()?, parts.parse::<u64>().ok()?)));
+    match parsed {
+        Some((part, parts)) if part < parts => Ok((part, parts)),
+        _ => Err("expected <part>/<parts> with part < parts".to_string()),
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<ExitCode> {
+    match matches.subcommand() {
+        Some(("play", matches)) => play(matches).map(|()| ExitCode::SUCCESS),
+        Some(("solve", matches)) => solve_command(matches).map(|()| ExitCode::SUCCESS),
+        Some(("verify", matches)) => verify_command(matches),
+        Some(("generate", matches)) => generate_command(matches).map(|()| ExitCode::SUCCESS),
+        _ => unreachable!("a subcommand is required"),
+    }
+}
+
+fn play(matches: &ArgMatches) -> Result<()> {
+    let address = format!("{}:{}", matches.get_one::<String>("server").unwrap(), matches.get_one::<u16>("port").unwrap());
+    let name = matches.get_one::<String>("name").unwrap();
+    let mut strategy = strategy_named(matches.get_one::<String>("strategy").unwrap()).expect("strategies are checked by the parser");
+    let (mut registry, timeout) = configured_registry(matches);
+    let timeout = timeout.or(Some(PLAY_TIMEOUT));
+    let unknown = UnknownPolicy::named(matches.get_one::<String>("unknown").unwrap()).expect("policies are checked by the parser");
+    let skip = cancel_on_enter();
+
+    let mut stream = TcpStream::connect(&address).map_err(|source| Error::Connection { address, source })?;
+    let codec = FrameCodec::default();
+    let mut state = GameState::new();
+    send(&mut stream, &codec, Message::Hello)?;
+    let server_version = match observed(receive(&mut stream, &codec, protocol::VERSION)?, &mut state) {
+        Message::Welcome(welcome) => welcome.version,
+        other => return Err(Error::unexpected("Welcome", other)),
+    };
+    let version = match negotiate(server_version, !*matches.get_one::<bool>("strict-version").unwrap()) {
+        Negotiation::Supported(version) => {
+            eprintln!("server speaks version {version}");
+            version
+        }
+        Negotiation::Degraded { server, version } => {
+            eprintln!("server speaks version {server}, newer than ours: speaking version {version}, challenges it does not know will not be solved");
+            version
+        }
+        Negotiation::Unsupported(server) => {
+            return Err(Error::Protocol(format!("the server speaks version {server}, this client speaks versions {SUPPORTED_VERSIONS:?}")));
+        }
+    };
+    let disabled = registry.retain_variants(challenge_kinds(version).expect("negotiated versions are supported"));
+    if !disabled.is_empty() {
+        eprintln!("challenges disabled by version {version}: {}", disabled.join(", "));
+    }
+    let mut candidates = name_policy(matches).candidates(name);
+    let mut candidate = name.clone();
+    loop {
+        send(&mut stream, &codec, observed(Message::Subscribe(Subscribe { name: candidate.clone() }), &mut state))?;
+        match observed(receive(&mut stream, &codec, version)?, &mut state) {
+            Message::SubscribeResult(SubscribeResult::Ok) => break,
+            Message::SubscribeResult(SubscribeResult::Err(err)) => {
+                eprintln!("the server refused the name {candidate:?}: {err:?}");
+                candidate = candidates.next(&err).ok_or_else(|| {
+                    Error::Protocol(format!("no acceptable name found, the server refused {:?} (see the --name-* options)", candidates.tried()))
+                })?;
+                eprintln!("subscribing as {candidate:?}");
+            }
+            other => return Err(Error::unexpected("SubscribeResult", other)),
+        }
+    }
+
+    loop {
+        match observed(receive(&mut stream, &codec, version)?, &mut state) {
+            Message::EndOfGame(..) => {
+                print!("{}", state.report());
+                return Ok(());
+            }
+            Message::Challenge(challenge) => {
+                let answer = if registry.solver(&challenge).is_some() {
+                    solve(&registry, challenge, &skippable(solve_limits(timeout), &skip), &mut log_progress)?
+                } else {
+                    match unsolvable(&challenge, unknown)? {
+                        Some(answer) => answer,
+                        None => continue,
+                    }
+                };
+                let next_target = strategy.choose(&state).unwrap_or_else(|| {
+                    eprintln!("nobody to pass the challenge to");
+                    String::new()
+                });
+                send(&mut stream, &codec, Message::ChallengeResult(ChallengeResult { answer, next_target }))?;
+            }
+            Message::Unknown(raw) if unknown == UnknownPolicy::Abort => return Err(Error::Protocol(format!("unknown message {raw}"))),
+            Message::Unknown(raw) => eprintln!("ignoring unknown message {raw}"),
+            _ => {}
+        }
+    }
+}
+
+/// Answer to a challenge no solver takes, an unknown one or one disabled by the protocol
+/// version, as `policy` says; `None` to leave it unanswered
+fn unsolvable(challenge: &Challenge, policy: UnknownPolicy) -> Result<Option<ChallengeAnswer>> {
+    let description = match challenge {
+        Challenge::Unknown(raw) => format!("unknown challenge {raw}"),
+        known => format!("{} challenge, which has no solver", variant_name(known)),
+    };
+    match policy {
+        UnknownPolicy::Ignore => {
+            eprintln!("leaving the {description} unanswered");
+            Ok(None)
+        }
+        UnknownPolicy::Fallback => {
+            eprintln!("sending a fallback answer to the {description}");
+            Ok(Some(ChallengeAnswer::fallback()))
+        }
+        UnknownPolicy::Abort => Err(Error::Protocol(format!("cannot answer the {description}"))),
+    }
+}
+
+fn name_policy(matches: &ArgMatches) -> NamePolicy {
+    NamePolicy::default()
+        .with_separator(matches.get_one::<String>("name-separator").unwrap())
+        .with_max_attempts(*matches.get_one::<usize>("name-attempts").unwrap())
+        .with_charset(Charset::named(matches.get_one::<String>("name-charset").unwrap()).expect("charsets are checked by the parser"))
+        .with_replacement(*matches.get_one::<Option<char>>("name-replacement").unwrap())
+        .with_max_length(*matches.get_one::<usize>("name-max-length").unwrap())
+}
+
+/// Feeds `message` to `state` on its way in or out
+fn observed(message: Message, state: &mut GameState) -> Message {
+    state.observe(&message);
+    message
+}
+
+fn solve_command(matches: &ArgMatches) -> Result<()> {
+    let challenge: Challenge = read_json(matches.get_one::<String>("challenge").unwrap(), "challenge")?;
+    let (registry, timeout) = configured_registry(matches);
+    let answer = solve(&registry, challenge, &solve_limits(timeout), &mut |_| {})?;
+    println!("{}", serde_json::to_string(&answer).expect("answers are always serializable"));
+    Ok(())
+}
+
+fn verify_command(matches: &ArgMatches) -> Result<ExitCode> {
+    let (challenge_path, answer_path) = (matches.get_one::<String>("challenge").unwrap(), matches.get_one::<String>("answer").unwrap());
+    if challenge_path == STDIN && answer_path == STDIN {
+        return Err(Error::Usage("the challenge and the answer cannot both be read from the standard input".to_string()));
+    }
+    let challenge: Challenge = read_json(challenge_path, "challenge")?;
+    let answer: ChallengeAnswer = read_json(answer_path, "answer")?;
+    let name = variant_name(&challenge);
+    match ChallengeRegistry::with_default_solvers().verify(challenge, &answer) {
+        Some(true) => {
+            println!("valid");
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(false) => {
+            println!("invalid");
+            Ok(ExitCode::FAILURE)
+        }
+        None => Err(Error::Solver(format!("no solver registered for {name}"))),
+    }
+}
+
+fn generate_command(matches: &ArgMatches) -> Result<()> {
+    let kinds: Vec<&str> = match matches.get_many::<String>("kind") {
+        Some(kinds) => kinds.map(String::as_str).collect(),
+        None => VARIANTS.to_vec(),
+    };
+    let mut rng = match matches.get_one::<u64>("seed") {
+        Some(&seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let settings = GeneratorSettings::default();
+    for kind in kinds.iter().cycle().take(*matches.get_one::<usize>("count").unwrap()) {
+        let challenge = generate_challenge(kind, &settings, &mut rng).expect("kinds are checked by the parser");
+        println!("{}", serde_json::to_string(&challenge).expect("challenges are always serializable"));
+    }
+    Ok(())
+}
+
+/// Solvers set up from the options of `solver_args`, and the solve timeout
+fn configured_registry(matches: &ArgMatches) -> (ChallengeRegistry, Option<Duration>) {
+    let dictionary = load_dictionary(matches.get_one::<String>("language").unwrap());
+    let threads = matches.get_one::<usize>("threads").copied();
+    let seed_range = matches.get_one::<SeedRange>("seed-range").map(|&range| match matches.get_one::<(u64, u64)>("share") {
+        Some(&share) => share_of(range, share),
+        None => range,
+    });
+    let timeout = matches.get_one::<u64>("timeout").map(|&timeout| Duration::from_millis(timeout));
+    (challenge_registry(dictionary, threads, seed_range), timeout)
+}
+
+/// Answer of the registry, logging why it may be wrong; a missing solver or a panic in a solver
+/// is a solver failure
+fn solve(registry: &ChallengeRegistry, challenge: Challenge, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Result<ChallengeAnswer> {
+    let name = variant_name(&challenge);
+    match panic::catch_unwind(AssertUnwindSafe(|| registry.answer(challenge, limits, progress))) {
+        Ok(Some(answered)) => {
+            if let Some(reason) = answered.stop_reason {
+                eprintln!("stopped early: {reason}");
+            }
+            if let Some(err) = &answered.invalid {
+                eprintln!("invalid challenge: {err}");
+            } else if !answered.verified {
+                eprintln!("no valid answer found for {name}");
+            }
+            Ok(answered.answer)
+        }
+        Ok(None) => Err(Error::Solver(format!("no solver registered for {name}"))),
+        Err(_) => Err(Error::Solver(format!("the {name} solver panicked"))),
+    }
+}
+
+/// Default solvers, with the settings given on the command line and more logging
+fn challenge_registry(dictionary: Option<Arc<Dictionary>>, threads: Option<usize>, seed_range: Option<SeedRange>) -> ChallengeRegistry {
+    let mut registry = ChallengeRegistry::with_default_solvers();
+    registry
+        .register(hash_cash_registration::<Md5>(threads, seed_range))
+        .register(hash_cash_registration::<Sha1>(threads, seed_range))
+        .register(hash_cash_registration::<Sha256>(threads, seed_range))
+        .register(Registration::<MonstrousMaze>::new().with_solver(|maze, limits, progress| {
+            eprintln!("{}x{} maze, endurance {}", maze.maze.width(), maze.maze.height(), maze.input.endurance);
+            let solved = maze.solve_within(limits, progress);
+            if let Solved::Answer(output) = &solved {
+                eprintln!("{}", maze.maze.render_path(maze.start_point, &output.path));
+            }
+            solved
+        }));
+    if let Some(dictionary) = dictionary {
+        registry.register(Registration::<RecoverSecret>::new().with_configuration(move |secret| secret.with_dictionary(dictionary.clone())));
+    }
+    registry
+}
+
+fn hash_cash_registration<H: HashAlgorithm>(threads: Option<usize>, seed_range: Option<SeedRange>) -> Registration<HashCash<H>> {
+    Registration::new()
+        .with_configuration(move |hash: HashCash<H>| {
+            let hash = match threads {
+                Some(threads) => hash.with_threads(threads),
+                None => hash,
+            };
+            match seed_range {
+                Some(seed_range) => hash.with_seed_range(seed_range),
+                None => hash,
+            }
+        })
+        .with_solver(|hash, limits, progress| {
+            let report = hash.solve_with_report(limits, progress);
+            eprintln!("{} seeds tried, resume from {}", report.seeds_tried, report.remaining);
+            report.into_solved()
+        })
+}
+
+/// Cancels the challenge being solved whenever a line is typed on the standard input. Only a
+/// terminal is watched, and its end never cancels anything, so that a closed or piped standard
+/// input does not cut a search short
+fn cancel_on_enter() -> Arc<Mutex<CancellationToken>> {
+    let current = Arc::new(Mutex::new(CancellationToken::new()));
+    if !io::stdin().is_terminal() {
+        return current;
+    }
+    let watched = current.clone();
+    thread::spawn(move || {
+        let mut line = String::new();
+        while let Ok(1..) = io::stdin().read_line(&mut line) {
+            watched.lock().unwrap_or_else(PoisonError::into_inner).cancel();
+            line.clear();
+        }
+    });
+    current
+}
+
+fn solve_limits(timeout: Option<Duration>) -> SolveLimits {
+    match timeout {
+        Some(timeout) => SolveLimits::default().with_timeout(timeout),
+        None => SolveLimits::default(),
+    }
+}
+
+/// `limits` for a new challenge, which is the one `skip` cancels from now on
+fn skippable(limits: SolveLimits, skip: &Mutex<CancellationToken>) -> SolveLimits {
+    let cancellation = CancellationToken::new();
+    *skip.lock().unwrap_or_else(PoisonError::into_inner) = cancellation.clone();
+    limits.with_cancellation(cancellation)
+}
+
+fn log_progress(progress: Progress) {
+    match progress {
+        Progress::Attempts(attempts) => eprintln!("{attempts} attempts"),
+        Progress::ExploredStates(states) => eprintln!("{states} states explored"),
+    }
+}
+
+/// The `part`-th of `parts` equal shares of `range`, so that several processes can split
+/// the same range without overlapping
+fn share_of(range: SeedRange, (part, parts): (u64, u64)) -> SeedRange {
+    let share = range.split(parts)[part as usize];
+    if share.is_empty() {
+        eprintln!("seed range share {part}/{parts} is empty");
+    }
+    share
+}
+
+fn load_dictionary(language: &str) -> Option<Arc<Dictionary>> {
+    let path = Dictionary::path_for_language(&Dictionary::default_directory(), language);
+    match Dictionary::load(&path) {
+        Ok(dictionary) if dictionary.is_empty() => {
+            eprintln!("word list for {language} is empty, secrets will not be ranked");
+            None
+        }
+        Ok(dictionary) => {
+            eprintln!("loaded {} {language} words", dictionary.len());
+            Some(Arc::new(dictionary))
+        }
+        Err(err) => {
+            eprintln!("warning: no word list for {language} at {} ({err}), secrets will not be ranked", path.display());
+            None
+        }
+    }
+}
+
+/// `what` read from the file at `path`, or from the standard input for `-`
+fn read_json<T: DeserializeOwned>(path: &str, what: &str) -> Result<T> {
+    let mut json = String::new();
+    let read = match path {
+        STDIN => io::stdin().read_to_string(&mut json).map(|_| ()),
+        path => fs::read_to_string(path).map(|content| json = content),
+    };
+    read.map_err(|err| Error::Usage(format!("cannot read the {what} from {path}: {err}")))?;
+    serde_json::from_str(&json).map_err(|err| Error::Usage(format!("{path} does not hold a {what}: {err}")))
+}
+
+/// Next message, decoded as a message of protocol `version`
+fn receive(stream: &mut TcpStream, codec: &FrameCodec, version: i32) -> Result<Message> {
+    let frame = codec.read_frame(stream)?;
+    eprintln!("received: {}", String::from_utf8_lossy(&frame));
+    Ok(decode_message_of(version, &frame)?)
+}
+
+fn send(stream: &mut TcpStream, codec: &FrameCodec, message_to_send: Message) -> Result<()> {
+    Ok(write_message(stream, codec, &message_to_send)?)
+}
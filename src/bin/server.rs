@@ -0,0 +1,66 @@
+use rustfinal::error::{Error, Result};
+use rustfinal::game_server::{GameConfig, GameServer};
+use rustfinal::protocol::{challenge_kinds, SUPPORTED_VERSIONS};
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::time::Duration;
+
+fn main() -> ExitCode {
+    match run(&cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn cli() -> Command<'static> {
+    Command::new("server")
+        .about("Plays one game with the players that connect, then prints the final leader board")
+        .arg(Arg::new("address").long("address").value_name("ADDRESS").default_value("127.0.0.1:7878").help("Address to listen on"))
+        .arg(Arg::new("players").long("players").value_name("COUNT").value_parser(value_parser!(usize)).default_value("2")
+            .help("Players to wait for before the first round"))
+        .arg(Arg::new("rounds").long("rounds").value_name("COUNT").value_parser(value_parser!(usize)).default_value("10").help("Rounds of the game"))
+        .arg(Arg::new("timeout").long("timeout").value_name("MS").value_parser(value_parser!(u64)).default_value("2000")
+            .help("Time a player has to answer a challenge"))
+        .arg(Arg::new("seed").long("seed").value_name("SEED").takes_value(true).value_parser(value_parser!(u64))
+            .help("Seed giving the same challenges on every run"))
+        .arg(Arg::new("protocol-version").long("protocol-version").value_name("VERSION").value_parser(parse_version).default_value("2")
+            .help("Protocol version announced to the players, only its challenges being dealt"))
+}
+
+fn parse_version(version: &str) -> std::result::Result<i32, String> {
+    let version: i32 = version.parse().map_err(|err| format!("{err}"))?;
+    match challenge_kinds(version) {
+        Some(_) => Ok(version),
+        None => Err(format!("expected one of {SUPPORTED_VERSIONS:?}")),
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<()> {
+    let address = matches.get_one::<String>("address").unwrap();
+    let config = GameConfig {
+        player_count: *matches.get_one::<usize>("players").unwrap(),
+        rounds: *matches.get_one::<usize>("rounds").unwrap(),
+        challenge_timeout: Duration::from_millis(*matches.get_one::<u64>("timeout").unwrap()),
+        seed: matches.get_one::<u64>("seed").copied(),
+        version: *matches.get_one::<i32>("protocol-version").unwrap(),
+        ..GameConfig::default()
+    };
+
+    let listener = TcpListener::bind(address).map_err(|source| Error::Connection { address: address.to_string(), source })?;
+    eprintln!("listening on {address}, waiting for {} players", config.player_count);
+
+    let mut server = GameServer::new(config).with_events(|event| eprintln!("{event}"));
+    server.accept_players(&listener)?;
+    let leader_board = server.play();
+    for player in leader_board.0 {
+        println!("{}: score {}, {} steps in {:.3}s", player.name, player.score, player.steps, player.total_used_time);
+    }
+    Ok(())
+}
+
@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::challenge_trait::{ChallengeGenerator, ChallengeTrait};
+use crate::hash_cash_challenge::generator::HashCashSettings;
+use crate::hash_cash_challenge::{MD5HashCash, SHA1HashCash, SHA256HashCash};
+use crate::monstrous_maze_challenge::generator::MazeSettings;
+use crate::monstrous_maze_challenge::MonstrousMaze;
+use crate::recover_secret_challenge::generator::RecoverSecretSettings;
+use crate::recover_secret_challenge::RecoverSecret;
+use crate::solve_control::{Progress, SolveLimits, Solved, StopReason};
+use crate::protocol::{Challenge, ChallengeAnswer};
+
+/// Links a challenge to its `Challenge` and `ChallengeAnswer` variants
+pub trait ProtocolChallenge: ChallengeTrait {
+    /// Name of the `Challenge` variant, as returned by `variant_name`
+    const VARIANT: &'static str;
+
+    /// The input when `challenge` is this challenge's variant
+    fn input(challenge: Challenge) -> Option<Self::Input>;
+    fn answer(output: Self::Output) -> ChallengeAnswer;
+    /// The output when `answer` is this challenge's variant
+    fn output(answer: &ChallengeAnswer) -> Option<&Self::Output>;
+}
+
+/// Settings of the generator of every challenge
+#[derive(Debug, Clone, Default)]
+pub struct GeneratorSettings {
+    pub hash_cash: HashCashSettings,
+    pub maze: MazeSettings,
+    pub recover_secret: RecoverSecretSettings,
+}
+
+/// Implements `ProtocolChallenge` for each challenge, named as its `Challenge` and
+/// `ChallengeAnswer` variants, and lists them all with the field of `GeneratorSettings` they
+/// are generated with
+macro_rules! protocol_challenges {
+    ($($challenge:ident => $settings:ident),* $(,)?) => {
+        $(
+            impl ProtocolChallenge for $challenge {
+                const VARIANT: &'static str = stringify!($challenge);
+
+                fn input(challenge: Challenge) -> Option<Self::Input> {
+                    match challenge {
+                        Challenge::$challenge(input) => Some(input),
+                        _ => None,
+                    }
+                }
+
+                fn answer(output: Self::Output) -> ChallengeAnswer {
+                    ChallengeAnswer::$challenge(output)
+                }
+
+                fn output(answer: &ChallengeAnswer) -> Option<&Self::Output> {
+                    match answer {
+                        ChallengeAnswer::$challenge(output) => Some(output),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+
+        /// Name of the variant of `challenge`, `Unknown` for a challenge of a newer protocol
+        pub fn variant_name(challenge: &Challenge) -> &'static str {
+            match challenge {
+                $(Challenge::$challenge(_) => $challenge::VARIANT,)*
+                Challenge::Unknown(_) => "Unknown",
+            }
+        }
+
+        /// Every `Challenge` variant, in the order the game server deals them
+        pub const VARIANTS: [&str; [$($challenge::VARIANT),*].len()] = [$($challenge::VARIANT),*];
+
+        /// A random challenge of the `variant` kind, `None` when there is no such variant
+        pub fn generate_challenge<R: Rng + ?Sized>(variant: &str, settings: &GeneratorSettings, rng: &mut R) -> Option<Challenge> {
+            match variant {
+                $($challenge::VARIANT => Some(Challenge::$challenge($challenge::generate(&settings.$settings, rng))),)*
+                _ => None,
+            }
+        }
+
+        impl ChallengeRegistry {
+            /// Every challenge solved with its default settings
+            pub fn with_default_solvers() -> Self {
+                let mut registry = ChallengeRegistry::default();
+                $(registry.register(Registration::<$challenge>::new());)*
+                registry
+            }
+        }
+    };
+}
+
+protocol_challenges! {
+    MD5HashCash => hash_cash,
+    MonstrousMaze => maze,
+    RecoverSecret => recover_secret,
+    SHA1HashCash => hash_cash,
+    SHA256HashCash => hash_cash,
+}
+
+/// What a solver made of a challenge: the answer to send, and why it may not solve the challenge
+#[derive(Debug, Clone)]
+pub struct Answered {
+    pub answer: ChallengeAnswer,
+    /// Why the search stopped before finding an answer
+    pub stop_reason: Option<StopReason>,
+    /// Why the challenge is invalid, `answer` being empty
+    pub invalid: Option<String>,
+    /// Whether `answer` solves the challenge
+    pub verified: bool,
+}
+
+/// Object-safe view of a `ChallengeTrait`, turning a `Challenge` into its `ChallengeAnswer`
+pub trait ChallengeSolver {
+    fn name(&self) -> String;
+    /// Answer to send for `challenge`, an empty one when the challenge is invalid or no
+    /// answer was found in time. `None` when `challenge` is not this solver's variant.
+    fn answer(&self, challenge: Challenge, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Option<Answered>;
+    /// Whether `answer` solves `challenge`, `None` when `challenge` is not this solver's variant
+    fn verify(&self, challenge: Challenge, answer: &ChallengeAnswer) -> Option<bool>;
+}
+
+type Configure<C> = Box<dyn Fn(C) -> C>;
+type Solve<C> = Box<dyn Fn(&C, &SolveLimits, &mut dyn FnMut(Progress)) -> Solved<<C as ChallengeTrait>::Output>>;
+
+/// How a challenge is solved: `solve_within` on the challenge built by `new`, unless
+/// `with_configuration` or `with_solver` say otherwise
+pub struct Registration<C: ChallengeTrait> {
+    configure: Configure<C>,
+    solve: Solve<C>,
+}
+
+impl<C: ChallengeTrait> Registration<C> {
+    pub fn new() -> Self {
+        Registration { configure: Box::new(|challenge| challenge), solve: Box::new(|challenge, limits, progress| challenge.solve_within(limits, progress)) }
+    }
+
+    /// Adjusts every new challenge before it is solved, e.g. with solver settings
+    pub fn with_configuration(mut self, configure: impl Fn(C) -> C + 'static) -> Self {
+        self.configure = Box::new(configure);
+        self
+    }
+
+    pub fn with_solver(mut self, solve: impl Fn(&C, &SolveLimits, &mut dyn FnMut(Progress)) -> Solved<C::Output> + 'static) -> Self {
+        self.solve = Box::new(solve);
+        self
+    }
+}
+
+impl<C: ChallengeTrait> Default for Registration<C> {
+    fn default() -> Self {
+        Registration::new()
+    }
+}
+
+impl<C> ChallengeSolver for Registration<C>
+where
+    C: ProtocolChallenge,
+    C::Output: Default,
+    C::Error: fmt::Display,
+{
+    fn name(&self) -> String {
+        C::name()
+    }
+
+    fn answer(&self, challenge: Challenge, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Option<Answered> {
+        let input = C::input(challenge)?;
+        let answered = match C::new(input) {
+            Ok(challenge) => {
+                let challenge = (self.configure)(challenge);
+                let solved = (self.solve)(&challenge, limits, progress);
+                let stop_reason = solved.stop_reason();
+                let output = solved.answer().unwrap_or_default();
+                let verified = challenge.verify(&output);
+                Answered { answer: C::answer(output), stop_reason, invalid: None, verified }
+            }
+            Err(err) => Answered { answer: C::answer(C::Output::default()), stop_reason: None, invalid: Some(err.to_string()), verified: false },
+        };
+        Some(answered)
+    }
+
+    fn verify(&self, challenge: Challenge, answer: &ChallengeAnswer) -> Option<bool> {
+        let input = C::input(challenge)?;
+        let Some(output) = C::output(answer) else { return Some(false) };
+        Some(C::new(input).is_ok_and(|challenge| challenge.verify(output)))
+    }
+}
+
+/// Solvers of the challenges this client can play, by `Challenge` variant
+#[derive(Default)]
+pub struct ChallengeRegistry {
+    solvers: HashMap<&'static str, Box<dyn ChallengeSolver>>,
+}
+
+impl ChallengeRegistry {
+    /// Solves `C` challenges as `registration` says, replacing any previous solver
+    pub fn register<C>(&mut self, registration: Registration<C>) -> &mut Self
+    where
+        C: ProtocolChallenge + 'static,
+        C::Output: Default,
+        C::Error: fmt::Display,
+    {
+        self.solvers.insert(C::VARIANT, Box::new(registration));
+        self
+    }
+
+    /// Drops the solvers of the variants out of `variants`, e.g. those a protocol version does not
+    /// have, and returns their names
+    pub fn retain_variants(&mut self, variants: &[&str]) -> Vec<&'static str> {
+        let mut disabled: Vec<&'static str> = self.solvers.keys().copied().filter(|variant| !variants.contains(variant)).collect();
+        disabled.sort_unstable();
+        for variant in &disabled {
+            self.solvers.remove(variant);
+        }
+        disabled
+    }
+
+    pub fn solver(&self, challenge: &Challenge) -> Option<&dyn ChallengeSolver> {
+        self.solvers.get(variant_name(challenge)).map(|solver| solver.as_ref())
+    }
+
+    /// Answer to `challenge`, `None` when no solver is registered for it
+    pub fn answer(&self, challenge: Challenge, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Option<Answered> {
+        self.solver(&challenge)?.answer(challenge, limits, progress)
+    }
+
+    /// Whether `answer` solves `challenge`, `None` when no solver is registered for it
+    pub fn verify(&self, challenge: Challenge, answer: &ChallengeAnswer) -> Option<bool> {
+        self.solver(&challenge)?.verify(challenge, answer)
+    }
+}
+
+#[cfg(test)]
+mod tests_challenge_registry {
+    use super::*;
+    use crate::hash_cash_challenge::{MD5HashCashInput, MD5HashCashOutput};
+    use crate::monstrous_maze_challenge::{MonstrousMazeInput, MonstrousMazeOutput};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn maze(grid: &str) -> Challenge {
+        Challenge::MonstrousMaze(MonstrousMazeInput { grid: grid.to_string(), endurance: 2 })
+    }
+
+    #[test]
+    fn is_challenge_registry_generated_answered() {
+        let registry = ChallengeRegistry::with_default_solvers();
+        let mut rng = StdRng::seed_from_u64(3);
+        for variant in VARIANTS {
+            let challenge = generate_challenge(variant, &GeneratorSettings::default(), &mut rng).unwrap();
+            assert_eq!(variant_name(&challenge), variant);
+            let answered = registry.answer(challenge.clone(), &SolveLimits::default(), &mut |_| {}).unwrap();
+            assert!(answered.verified && answered.stop_reason.is_none(), "{variant}");
+            assert_eq!(registry.verify(challenge, &answered.answer), Some(true), "{variant}");
+        }
+        assert!(generate_challenge("Sudoku", &GeneratorSettings::default(), &mut rng).is_none());
+    }
+
+    #[test]
+    fn is_challenge_registry_restricted() {
+        let mut kinds = VARIANTS;
+        let mut latest = crate::protocol::CHALLENGE_KINDS;
+        kinds.sort_unstable();
+        latest.sort_unstable();
+        assert_eq!(kinds, latest);
+
+        let mut registry = ChallengeRegistry::with_default_solvers();
+        assert!(registry.retain_variants(&VARIANTS).is_empty());
+        assert_eq!(registry.retain_variants(&["MonstrousMaze", "Sudoku"]), ["MD5HashCash", "RecoverSecret", "SHA1HashCash", "SHA256HashCash"]);
+        assert!(registry.answer(maze("│Y X│"), &SolveLimits::default(), &mut |_| {}).is_some());
+        let hash_cash = Challenge::MD5HashCash(MD5HashCashInput { complexity: 1, message: "hi".to_string() });
+        assert!(registry.answer(hash_cash, &SolveLimits::default(), &mut |_| {}).is_none());
+    }
+
+    #[test]
+    fn is_challenge_registry_answer() {
+        let registry = ChallengeRegistry::with_default_solvers();
+        let answer = registry.answer(maze("│Y X│"), &SolveLimits::default(), &mut |_| {}).map(|answered| answered.answer);
+        assert!(matches!(answer, Some(ChallengeAnswer::MonstrousMaze(MonstrousMazeOutput { path })) if path == ">>"));
+
+        let hash_cash = Challenge::MD5HashCash(MD5HashCashInput { complexity: 8, message: "hello".to_string() });
+        assert_eq!(registry.solver(&hash_cash).map(|solver| solver.name()), Some("hashCash".to_string()));
+        let Some(ChallengeAnswer::MD5HashCash(output)) = registry.answer(hash_cash, &SolveLimits::default(), &mut |_| {}).map(|answered| answered.answer) else { panic!("not a hash cash answer") };
+        let check = MD5HashCash::new(MD5HashCashInput { complexity: 8, message: "hello".to_string() }).unwrap();
+        assert!(check.verify(&output));
+    }
+
+    #[test]
+    fn is_challenge_registry_invalid_challenge() {
+        let registry = ChallengeRegistry::with_default_solvers();
+        let answered = registry.answer(maze("│Y  │"), &SolveLimits::default(), &mut |_| {}).unwrap();
+        assert!(matches!(answered.answer, ChallengeAnswer::MonstrousMaze(MonstrousMazeOutput { path }) if path.is_empty()));
+        assert!(answered.invalid.is_some() && !answered.verified);
+
+        let unreachable = Challenge::MonstrousMaze(MonstrousMazeInput { grid: "│YMX│".to_string(), endurance: 1 });
+        let answered = registry.answer(unreachable, &SolveLimits::default(), &mut |_| {}).unwrap();
+        assert_eq!((answered.stop_reason, answered.invalid, answered.verified), (Some(StopReason::Exhausted), None, false));
+    }
+
+    #[test]
+    fn is_challenge_registry_verify() {
+        let registry = ChallengeRegistry::with_default_solvers();
+        let path = |path: &str| ChallengeAnswer::MonstrousMaze(MonstrousMazeOutput { path: path.to_string() });
+        assert_eq!(registry.verify(maze("│Y X│"), &path(">>")), Some(true));
+        assert_eq!(registry.verify(maze("│Y X│"), &path(">")), Some(false));
+        let hash_cash = ChallengeAnswer::MD5HashCash(MD5HashCashOutput::default());
+        assert_eq!(registry.verify(maze("│Y X│"), &hash_cash), Some(false));
+        assert_eq!(ChallengeRegistry::default().verify(maze("│Y X│"), &path(">>")), None);
+    }
+
+    #[test]
+    fn is_challenge_registry_registration() {
+        let mut registry = ChallengeRegistry::default();
+        assert!(registry.answer(maze("│Y X│"), &SolveLimits::default(), &mut |_| {}).is_none());
+
+        registry.register(Registration::<MonstrousMaze>::new().with_solver(|_, _, _| Solved::Answer(MonstrousMazeOutput { path: "<".to_string() })));
+        let answered = registry.answer(maze("│Y X│"), &SolveLimits::default(), &mut |_| {}).unwrap();
+        assert!(matches!(answered.answer, ChallengeAnswer::MonstrousMaze(MonstrousMazeOutput { path }) if path == "<"));
+        assert!(!answered.verified);
+    }
+}
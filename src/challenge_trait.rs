@@ -0,0 +1,37 @@
+use crate::solve_control::{Progress, SolveLimits, Solved};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+pub trait ChallengeTrait: Sized {
+    /// Données en entrée du challenge
+    type Input;
+    /// Données en sortie du challenge
+    type Output;
+    /// Erreur levée quand l'entrée ne décrit pas un challenge valide
+    type Error;
+    /// Nom du challenge
+    fn name() -> String;
+    /// Create a challenge from the specific input
+    fn new(input: Self::Input) -> Result<Self, Self::Error>;
+    /// Résout le challenge
+    fn solve(&self) -> Self::Output;
+    /// Résout le challenge en s'arrêtant à l'échéance ou sur annulation, `progress` recevant
+    /// l'avancement de la recherche
+    fn solve_within(&self, _limits: &SolveLimits, _progress: &mut dyn FnMut(Progress)) -> Solved<Self::Output> {
+        Solved::Answer(self.solve())
+    }
+    /// Vérifie qu'une sortie est valide pour le challenge
+    fn verify(&self, answer: &Self::Output) -> bool;
+}
+
+/// Challenge that can be created at random, e.g. by a server or to stress-test a solver
+pub trait ChallengeGenerator: ChallengeTrait {
+    /// Paramètres de génération (taille, difficulté…)
+    type Settings;
+    /// Génère l'entrée d'un challenge avec `rng`
+    fn generate<R: Rng + ?Sized>(settings: &Self::Settings, rng: &mut R) -> Self::Input;
+    /// Génère toujours la même entrée pour la même graine
+    fn generate_seeded(settings: &Self::Settings, seed: u64) -> Self::Input {
+        Self::generate(settings, &mut StdRng::seed_from_u64(seed))
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::io;
+
+use crate::frame_codec::FrameError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Everything that can stop a client or a server, by category so that each has its own exit code
+#[derive(Debug)]
+pub enum Error {
+    /// Wrong command line arguments
+    Usage(String),
+    /// The server cannot be reached, or the server cannot listen
+    Connection { address: String, source: io::Error },
+    /// The connection broke or sent a frame that cannot be read
+    Framing(FrameError),
+    /// A frame holds something else than a protocol message, or a message cannot be encoded
+    Decoding(FrameError),
+    /// A valid message arrived when the protocol expects another one
+    Protocol(String),
+    /// A challenge could not be answered
+    Solver(String),
+}
+
+impl Error {
+    /// Process exit code, distinct for each category
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage(_) => 2,
+            Error::Connection { .. } => 3,
+            Error::Framing(_) => 4,
+            Error::Decoding(_) => 5,
+            Error::Protocol(_) => 6,
+            Error::Solver(_) => 7,
+        }
+    }
+
+    pub fn unexpected(expected: &str, received: impl fmt::Debug) -> Self {
+        Error::Protocol(format!("expected {expected}, received {received:?}"))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(usage) => write!(f, "{usage}"),
+            Error::Connection { address, source } => write!(f, "cannot connect to {address}: {source} (is the server running, and is the address <host>:<port>?)"),
+            Error::Framing(FrameError::Eof) => write!(f, "the peer closed the connection"),
+            Error::Framing(err) => write!(f, "connection lost: {err}"),
+            Error::Decoding(err) => write!(f, "{err} (client and server may not speak the same protocol version)"),
+            Error::Protocol(message) => write!(f, "protocol violation: {message}"),
+            Error::Solver(message) => write!(f, "solver failure: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Connection { source, .. } => Some(source),
+            Error::Framing(err) | Error::Decoding(err) => Some(err),
+            Error::Usage(_) | Error::Protocol(_) | Error::Solver(_) => None,
+        }
+    }
+}
+
+impl From<FrameError> for Error {
+    fn from(err: FrameError) -> Self {
+        match err {
+            FrameError::Eof | FrameError::Io(_) | FrameError::Oversized { .. } => Error::Framing(err),
+            FrameError::InvalidUtf8(_) | FrameError::Decode(_) | FrameError::Encode(_) => Error::Decoding(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::from(FrameError::from(err))
+    }
+}
+
+#[cfg(test)]
+mod tests_error {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn is_error_exit_code_distinct() {
+        let errors = [
+            Error::Usage(String::new()),
+            Error::Connection { address: String::new(), source: io::ErrorKind::ConnectionRefused.into() },
+            Error::Framing(FrameError::Eof),
+            Error::Decoding(FrameError::Decode(serde_json::from_str::<u8>("{").unwrap_err())),
+            Error::Protocol(String::new()),
+            Error::Solver(String::new()),
+        ];
+        let codes: HashSet<u8> = errors.iter().map(Error::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+    }
+
+    #[test]
+    fn is_error_from_frame_error() {
+        assert!(matches!(Error::from(FrameError::Eof), Error::Framing(_)));
+        assert!(matches!(Error::from(io::Error::from(io::ErrorKind::UnexpectedEof)), Error::Framing(FrameError::Eof)));
+        assert!(matches!(Error::from(FrameError::Oversized { size: 2, max: 1 }), Error::Framing(_)));
+        let decode = serde_json::from_str::<u8>("{").unwrap_err();
+        assert!(matches!(Error::from(FrameError::Decode(decode)), Error::Decoding(_)));
+    }
+}
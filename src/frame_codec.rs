@@ -0,0 +1,152 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str;
+
+/// Default upper bound for a single frame (16 MiB).
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+const HEADER_SIZE: usize = 4;
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// The peer closed the connection before a whole frame was read
+    Eof,
+    /// Any other I/O failure on the underlying stream
+    Io(io::Error),
+    /// The announced (or outgoing) frame is larger than the configured limit
+    Oversized { size: usize, max: usize },
+    /// The frame body is not valid UTF-8
+    InvalidUtf8(str::Utf8Error),
+    /// The frame body is not a valid JSON message
+    Decode(serde_json::Error),
+    /// The message could not be serialized
+    Encode(serde_json::Error),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::Eof => write!(f, "connection closed by peer"),
+            FrameError::Io(err) => write!(f, "i/o error: {err}"),
+            FrameError::Oversized { size, max } => write!(f, "frame of {size} bytes exceeds the limit of {max} bytes"),
+            FrameError::InvalidUtf8(err) => write!(f, "frame is not valid UTF-8: {err}"),
+            FrameError::Decode(err) => write!(f, "cannot decode message: {err}"),
+            FrameError::Encode(err) => write!(f, "cannot encode message: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl From<io::Error> for FrameError {
+    fn from(err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            FrameError::Eof
+        } else {
+            FrameError::Io(err)
+        }
+    }
+}
+
+/// Reads and writes frames made of a 4-byte big-endian length followed by the payload.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameCodec {
+    max_frame_size: usize,
+}
+
+impl FrameCodec {
+    pub fn new(max_frame_size: usize) -> Self {
+        FrameCodec { max_frame_size }
+    }
+
+    fn check_size(&self, size: usize) -> Result<(), FrameError> {
+        if size > self.max_frame_size || size > u32::MAX as usize {
+            return Err(FrameError::Oversized { size, max: self.max_frame_size });
+        }
+        Ok(())
+    }
+
+    /// Reads one whole frame, waiting for as many segments as needed.
+    pub fn read_frame<R: Read>(&self, reader: &mut R) -> Result<Vec<u8>, FrameError> {
+        let mut header = [0; HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+
+        let size = u32::from_be_bytes(header) as usize;
+        self.check_size(size)?;
+
+        let mut payload = vec![0; size];
+        reader.read_exact(&mut payload)?;
+        Ok(payload)
+    }
+
+    /// Writes the header and the payload, then flushes the writer.
+    pub fn write_frame<W: Write>(&self, writer: &mut W, payload: &[u8]) -> Result<(), FrameError> {
+        self.check_size(payload.len())?;
+
+        let mut frame = Vec::with_capacity(HEADER_SIZE + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(payload);
+        writer.write_all(&frame)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        FrameCodec::new(DEFAULT_MAX_FRAME_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests_frame_codec {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Hands out at most one byte per `read`, like a stream split in many segments
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn is_frame_codec_round_trip() {
+        let codec = FrameCodec::default();
+        let mut buffer = vec![];
+        codec.write_frame(&mut buffer, b"\"Hello\"").unwrap();
+        assert_eq!(&buffer[..4], &[0, 0, 0, 7]);
+        assert_eq!(codec.read_frame(&mut Cursor::new(buffer)).unwrap(), b"\"Hello\"");
+    }
+
+    #[test]
+    fn is_frame_codec_reading_short_reads() {
+        let codec = FrameCodec::default();
+        let mut buffer = vec![];
+        let payload = "x".repeat(5000);
+        codec.write_frame(&mut buffer, payload.as_bytes()).unwrap();
+        assert_eq!(codec.read_frame(&mut Trickle(&buffer)).unwrap(), payload.as_bytes());
+    }
+
+    #[test]
+    fn is_frame_codec_eof() {
+        let codec = FrameCodec::default();
+        assert!(matches!(codec.read_frame(&mut Cursor::new(vec![])), Err(FrameError::Eof)));
+        assert!(matches!(codec.read_frame(&mut Cursor::new(vec![0, 0, 0, 9, b'{'])), Err(FrameError::Eof)));
+    }
+
+    #[test]
+    fn is_frame_codec_oversized() {
+        let codec = FrameCodec::new(8);
+        let result = codec.read_frame(&mut Cursor::new(vec![0, 0, 1, 0]));
+        assert!(matches!(result, Err(FrameError::Oversized { size: 256, max: 8 })));
+        assert!(matches!(codec.write_frame(&mut vec![], &[0; 9]), Err(FrameError::Oversized { .. })));
+    }
+}
@@ -0,0 +1,462 @@
+use std::fmt;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::challenge_registry::{generate_challenge, variant_name, ChallengeRegistry, GeneratorSettings};
+use crate::error::{Error, Result};
+use crate::frame_codec::{FrameCodec, FrameError};
+use crate::protocol::{self, challenge_kinds, read_message, write_message, Challenge, ChallengeValue, EndOfGame, Message, PublicLeaderBoard, PublicPlayer, ReportedChallengeResult, RoundSummary, SubscribeError, SubscribeResult, Welcome};
+
+pub const MAX_NAME_LENGTH: usize = 32;
+/// Time a new connection has to say `Hello` and subscribe
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_SUBSCRIBE_ATTEMPTS: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    /// Players to wait for before the first round
+    pub player_count: usize,
+    pub rounds: usize,
+    /// Time a player has to answer a challenge
+    pub challenge_timeout: Duration,
+    /// Longest chain of a round, so that players passing a challenge around cannot stall the game
+    pub max_chain_length: usize,
+    /// Seed of the generated challenges, so that a game can be replayed
+    pub seed: Option<u64>,
+    /// Protocol version announced in `Welcome`, one of `SUPPORTED_VERSIONS`: only the challenges
+    /// of this version are dealt
+    pub version: i32,
+    pub generators: GeneratorSettings,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            player_count: 2,
+            rounds: 10,
+            challenge_timeout: Duration::from_secs(2),
+            max_chain_length: 16,
+            seed: None,
+            version: protocol::VERSION,
+            generators: GeneratorSettings::default(),
+        }
+    }
+}
+
+/// `Ok` when `name` may subscribe, given the names already taken
+pub fn validate_name<'a>(name: &str, mut taken: impl Iterator<Item = &'a str>) -> std::result::Result<(), SubscribeError> {
+    if name.trim().is_empty() || name.chars().count() > MAX_NAME_LENGTH || name.chars().any(char::is_control) {
+        return Err(SubscribeError::InvalidName);
+    }
+    if taken.any(|taken| taken == name) {
+        return Err(SubscribeError::AlreadyRegistered);
+    }
+    Ok(())
+}
+
+struct Player {
+    name: String,
+    stream_id: String,
+    stream: TcpStream,
+    score: i32,
+    steps: u32,
+    is_active: bool,
+    total_used_time: f64,
+}
+
+impl Player {
+    fn public(&self) -> PublicPlayer {
+        PublicPlayer {
+            name: self.name.clone(),
+            stream_id: self.stream_id.clone(),
+            score: self.score,
+            steps: self.steps,
+            is_active: self.is_active,
+            total_used_time: self.total_used_time,
+        }
+    }
+}
+
+/// Something that happened during a game, given to the `GameServer::with_events` callback
+#[derive(Debug)]
+pub enum GameEvent {
+    Subscribed { name: String, stream_id: String },
+    HandshakeFailed { address: String, error: Error },
+    /// Every player was dropped before the last round
+    NoActivePlayer { round: usize },
+    /// The player answered a challenge with another message than `ChallengeResult`
+    UnexpectedAnswer { name: String, message: Message },
+    TimedOut { name: String },
+    Unreachable { name: String, error: FrameError },
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::Subscribed { name, stream_id } => write!(f, "{name} subscribed from {stream_id}"),
+            GameEvent::HandshakeFailed { address, error } => write!(f, "handshake with {address} failed: {error}"),
+            GameEvent::NoActivePlayer { round } => write!(f, "no active player left after {round} rounds"),
+            GameEvent::UnexpectedAnswer { name, message } => write!(f, "{name} answered with {message:?}"),
+            GameEvent::TimedOut { name } => write!(f, "{name} timed out"),
+            GameEvent::Unreachable { name, error } => write!(f, "{name} is unreachable: {error}"),
+        }
+    }
+}
+
+type OnEvent = Box<dyn FnMut(&GameEvent) + Send>;
+
+/// Reference server playing the whole game with the players of one listener.
+///
+/// Each round hands a challenge to the active players in turn. Whoever solves it passes
+/// it on to its `next_target`, until someone fails, a target cannot be reached or the
+/// chain is `max_chain_length` long. A failing player loses a point, and the player who
+/// passed it the challenge wins one.
+pub struct GameServer {
+    config: GameConfig,
+    codec: FrameCodec,
+    registry: ChallengeRegistry,
+    rng: StdRng,
+    players: Vec<Player>,
+    on_event: OnEvent,
+}
+
+impl GameServer {
+    pub fn new(config: GameConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        GameServer { config, codec: FrameCodec::default(), registry: ChallengeRegistry::with_default_solvers(), rng, players: vec![], on_event: Box::new(|_| {}) }
+    }
+
+    /// Calls `on_event` with every event of the game, e.g. to log them; they are dropped by default
+    pub fn with_events(mut self, on_event: impl FnMut(&GameEvent) + Send + 'static) -> Self {
+        self.on_event = Box::new(on_event);
+        self
+    }
+
+    /// Accepts connections until `player_count` players are subscribed, dropping those
+    /// that fail the handshake
+    pub fn accept_players(&mut self, listener: &TcpListener) -> Result<()> {
+        while self.players.len() < self.config.player_count {
+            let (stream, address) = listener.accept()?;
+            match self.handshake(stream, address.to_string()) {
+                Ok(player) => {
+                    (self.on_event)(&GameEvent::Subscribed { name: player.name.clone(), stream_id: player.stream_id.clone() });
+                    self.players.push(player);
+                }
+                Err(error) => (self.on_event)(&GameEvent::HandshakeFailed { address: address.to_string(), error }),
+            }
+        }
+        Ok(())
+    }
+
+    fn handshake(&self, mut stream: TcpStream, stream_id: String) -> Result<Player> {
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        match read_message(&mut stream, &self.codec)? {
+            Message::Hello => write_message(&mut stream, &self.codec, &Message::Welcome(Welcome { version: self.config.version }))?,
+            other => return Err(Error::unexpected("Hello", other)),
+        }
+
+        for _ in 0..MAX_SUBSCRIBE_ATTEMPTS {
+            let name = match read_message(&mut stream, &self.codec)? {
+                Message::Subscribe(subscribe) => subscribe.name,
+                other => return Err(Error::unexpected("Subscribe", other)),
+            };
+            match validate_name(&name, self.players.iter().map(|player| player.name.as_str())) {
+                Ok(()) => {
+                    write_message(&mut stream, &self.codec, &Message::SubscribeResult(SubscribeResult::Ok))?;
+                    stream.set_read_timeout(None)?;
+                    return Ok(Player { name, stream_id, stream, score: 0, steps: 0, is_active: true, total_used_time: 0.0 });
+                }
+                Err(err) => write_message(&mut stream, &self.codec, &Message::SubscribeResult(SubscribeResult::Err(err)))?,
+            }
+        }
+        Err(Error::Protocol(format!("no acceptable name after {MAX_SUBSCRIBE_ATTEMPTS} attempts")))
+    }
+
+    pub fn leader_board(&self) -> PublicLeaderBoard {
+        PublicLeaderBoard(self.players.iter().map(Player::public).collect())
+    }
+
+    /// Plays every round then sends `EndOfGame`, returning the final leader board
+    pub fn play(&mut self) -> PublicLeaderBoard {
+        for round in 0..self.config.rounds {
+            let active: Vec<usize> = (0..self.players.len()).filter(|&index| self.players[index].is_active).collect();
+            if active.is_empty() {
+                (self.on_event)(&GameEvent::NoActivePlayer { round });
+                break;
+            }
+            self.broadcast(&Message::PublicLeaderBoard(self.leader_board()));
+            let challenge = self.challenge(round);
+            let summary = self.play_round(active[round % active.len()], challenge);
+            self.broadcast(&Message::RoundSummary(summary));
+        }
+        let leader_board = self.leader_board();
+        self.broadcast(&Message::EndOfGame(EndOfGame { leader_board: leader_board.clone() }));
+        leader_board
+    }
+
+    /// A generated challenge of the server's version, of a different kind each round
+    fn challenge(&mut self, round: usize) -> Challenge {
+        let kinds = challenge_kinds(self.config.version).expect("the server speaks a supported version");
+        generate_challenge(kinds[round % kinds.len()], &self.config.generators, &mut self.rng).expect("every variant has a generator")
+    }
+
+    fn play_round(&mut self, first: usize, challenge: Challenge) -> RoundSummary {
+        let mut chain = vec![];
+        let mut target = first;
+        let mut sender: Option<usize> = None;
+        for _ in 0..self.config.max_chain_length {
+            let value = self.submit(target, &challenge);
+            chain.push(ReportedChallengeResult { name: self.players[target].name.clone(), value: value.clone() });
+            let next_target = match value {
+                ChallengeValue::Ok { next_target, .. } => next_target,
+                ChallengeValue::BadResult { .. } | ChallengeValue::Timeout => {
+                    self.players[target].score -= 1;
+                    if let Some(sender) = sender {
+                        self.players[sender].score += 1;
+                    }
+                    break;
+                }
+                ChallengeValue::Unreachable => break,
+            };
+            match self.players.iter().position(|player| player.is_active && player.name == next_target) {
+                Some(next) => {
+                    sender = Some(target);
+                    target = next;
+                }
+                None => {
+                    chain.push(ReportedChallengeResult { name: next_target, value: ChallengeValue::Unreachable });
+                    break;
+                }
+            }
+        }
+        RoundSummary { challenge: variant_name(&challenge).to_string(), chain }
+    }
+
+    /// Sends `challenge` to a player and checks its answer. A player that times out is
+    /// dropped, since a late answer could not be told apart from the next one.
+    fn submit(&mut self, target: usize, challenge: &Challenge) -> ChallengeValue {
+        let player = &mut self.players[target];
+        let sent = write_message(&mut player.stream, &self.codec, &Message::Challenge(challenge.clone()))
+            .and_then(|()| player.stream.set_read_timeout(Some(self.config.challenge_timeout)).map_err(FrameError::from));
+        if let Err(error) = sent {
+            (self.on_event)(&GameEvent::Unreachable { name: player.name.clone(), error });
+            player.is_active = false;
+            return ChallengeValue::Unreachable;
+        }
+
+        let start = Instant::now();
+        let received = read_message(&mut player.stream, &self.codec);
+        let used_time = start.elapsed().as_secs_f64();
+        player.steps += 1;
+        player.total_used_time += used_time;
+        match received {
+            Ok(Message::ChallengeResult(result)) => {
+                if self.registry.verify(challenge.clone(), &result.answer) == Some(true) {
+                    ChallengeValue::Ok { used_time, next_target: result.next_target }
+                } else {
+                    ChallengeValue::BadResult { used_time, next_target: result.next_target }
+                }
+            }
+            Ok(message) => {
+                (self.on_event)(&GameEvent::UnexpectedAnswer { name: player.name.clone(), message });
+                ChallengeValue::BadResult { used_time, next_target: String::new() }
+            }
+            Err(FrameError::Io(err)) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                (self.on_event)(&GameEvent::TimedOut { name: player.name.clone() });
+                player.is_active = false;
+                ChallengeValue::Timeout
+            }
+            Err(error) => {
+                (self.on_event)(&GameEvent::Unreachable { name: player.name.clone(), error });
+                player.is_active = false;
+                ChallengeValue::Unreachable
+            }
+        }
+    }
+
+    fn broadcast(&mut self, message: &Message) {
+        for player in self.players.iter_mut().filter(|player| player.is_active) {
+            if let Err(error) = write_message(&mut player.stream, &self.codec, message) {
+                (self.on_event)(&GameEvent::Unreachable { name: player.name.clone(), error });
+                player.is_active = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_game_server {
+    use super::*;
+    use crate::hash_cash_challenge::generator::HashCashSettings;
+    use crate::protocol::{ChallengeAnswer, ChallengeResult, Subscribe};
+    use crate::solve_control::SolveLimits;
+    use crate::monstrous_maze_challenge::MonstrousMazeOutput;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    enum Behaviour {
+        Solve,
+        AnswerBadly,
+        Sleep,
+    }
+
+    /// Plays until `EndOfGame` or a closed connection, passing every challenge to `target`
+    fn bot(address: SocketAddr, name: &str, target: &str, behaviour: Behaviour) -> thread::JoinHandle<Vec<Message>> {
+        let (name, target) = (name.to_string(), target.to_string());
+        thread::spawn(move || {
+            let codec = FrameCodec::default();
+            let registry = ChallengeRegistry::with_default_solvers();
+            let mut stream = TcpStream::connect(address).unwrap();
+            write_message(&mut stream, &codec, &Message::Hello).unwrap();
+            write_message(&mut stream, &codec, &Message::Subscribe(Subscribe { name })).unwrap();
+            let mut received = vec![];
+            while let Ok(message) = read_message(&mut stream, &codec) {
+                if let Message::Challenge(challenge) = &message {
+                    let answer = match behaviour {
+                        Behaviour::Solve => registry.answer(challenge.clone(), &SolveLimits::default(), &mut |_| {}).unwrap().answer,
+                        Behaviour::AnswerBadly => ChallengeAnswer::MonstrousMaze(MonstrousMazeOutput::default()),
+                        Behaviour::Sleep => {
+                            thread::sleep(Duration::from_millis(500));
+                            break;
+                        }
+                    };
+                    write_message(&mut stream, &codec, &Message::ChallengeResult(ChallengeResult { answer, next_target: target.clone() })).unwrap();
+                }
+                let end = matches!(message, Message::EndOfGame(_));
+                received.push(message);
+                if end {
+                    break;
+                }
+            }
+            received
+        })
+    }
+
+    fn server(config: GameConfig) -> (SocketAddr, thread::JoinHandle<PublicLeaderBoard>) {
+        let (address, handle, _) = logged_server(config);
+        (address, handle)
+    }
+
+    /// Server recording the text of its events
+    fn logged_server(config: GameConfig) -> (SocketAddr, thread::JoinHandle<PublicLeaderBoard>, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let events = Arc::new(Mutex::new(vec![]));
+        let log = events.clone();
+        let handle = thread::spawn(move || {
+            let mut server = GameServer::new(config).with_events(move |event| log.lock().unwrap().push(event.to_string()));
+            server.accept_players(&listener).unwrap();
+            server.play()
+        });
+        (address, handle, events)
+    }
+
+    fn hash_cash_complexity(complexity: std::ops::RangeInclusive<u32>) -> GeneratorSettings {
+        GeneratorSettings { hash_cash: HashCashSettings { message: None, complexity }, ..GeneratorSettings::default() }
+    }
+
+    fn summaries(messages: &[Message]) -> Vec<&RoundSummary> {
+        messages.iter().filter_map(|message| match message {
+            Message::RoundSummary(summary) => Some(summary),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn is_game_server_name_validated() {
+        let taken = ["alice", "bob"];
+        assert_eq!(validate_name("carol", taken.into_iter()), Ok(()));
+        assert_eq!(validate_name("bob", taken.into_iter()), Err(SubscribeError::AlreadyRegistered));
+        assert_eq!(validate_name("  ", taken.into_iter()), Err(SubscribeError::InvalidName));
+        assert_eq!(validate_name("bo\nb", taken.into_iter()), Err(SubscribeError::InvalidName));
+        assert_eq!(validate_name(&"a".repeat(MAX_NAME_LENGTH + 1), taken.into_iter()), Err(SubscribeError::InvalidName));
+    }
+
+    #[test]
+    fn is_game_server_full_game() {
+        let config = GameConfig { rounds: 3, max_chain_length: 4, seed: Some(1), generators: hash_cash_complexity(4..=8), ..GameConfig::default() };
+        let (address, server) = server(config);
+        let alice = bot(address, "alice", "bob", Behaviour::Solve);
+        let bob = bot(address, "bob", "alice", Behaviour::AnswerBadly);
+        let (alice, bob, leader_board) = (alice.join().unwrap(), bob.join().unwrap(), server.join().unwrap());
+
+        assert!(matches!(alice[0], Message::Welcome(Welcome { version: protocol::VERSION })));
+        assert!(matches!(alice[1], Message::SubscribeResult(SubscribeResult::Ok)));
+        assert!(matches!(alice.last(), Some(Message::EndOfGame(_))));
+        assert!(matches!(bob.last(), Some(Message::EndOfGame(_))));
+
+        let summaries = summaries(&alice);
+        assert_eq!(summaries.iter().map(|summary| summary.challenge.as_str()).collect::<Vec<_>>(), ["MD5HashCash", "MonstrousMaze", "RecoverSecret"]);
+        for summary in summaries {
+            let last = summary.chain.last().unwrap();
+            assert_eq!(last.name, "bob");
+            assert!(matches!(last.value, ChallengeValue::BadResult { .. }));
+            assert!(summary.chain.iter().rev().skip(1).all(|result| matches!(result.value, ChallengeValue::Ok { .. })));
+        }
+
+        let bob = leader_board.0.iter().find(|player| player.name == "bob").unwrap();
+        assert_eq!(bob.score, -3);
+        assert_eq!(bob.steps, 3);
+        assert!(leader_board.0.iter().all(|player| player.is_active));
+    }
+
+    #[test]
+    fn is_game_server_version_dealt() {
+        let mut server = GameServer::new(GameConfig { seed: Some(1), version: protocol::v1::VERSION, generators: hash_cash_complexity(4..=4), ..GameConfig::default() });
+        let dealt: Vec<&str> = (0..6).map(|round| variant_name(&server.challenge(round))).collect();
+        assert_eq!(dealt, ["MD5HashCash", "MonstrousMaze", "RecoverSecret", "MD5HashCash", "MonstrousMaze", "RecoverSecret"]);
+
+        let mut server = GameServer::new(GameConfig { seed: Some(1), generators: hash_cash_complexity(4..=4), ..GameConfig::default() });
+        assert_eq!(variant_name(&server.challenge(4)), "SHA256HashCash");
+    }
+
+    #[test]
+    fn is_game_server_subscribe_errors() {
+        let (address, server) = server(GameConfig { player_count: 1, rounds: 0, ..GameConfig::default() });
+        let codec = FrameCodec::default();
+        let mut stream = TcpStream::connect(address).unwrap();
+        write_message(&mut stream, &codec, &Message::Hello).unwrap();
+        assert!(matches!(read_message(&mut stream, &codec), Ok(Message::Welcome(_))));
+        for (name, expected) in [("", SubscribeResult::Err(SubscribeError::InvalidName)), ("alice", SubscribeResult::Ok)] {
+            write_message(&mut stream, &codec, &Message::Subscribe(Subscribe { name: name.to_string() })).unwrap();
+            assert!(matches!(read_message(&mut stream, &codec), Ok(Message::SubscribeResult(result)) if result == expected));
+        }
+        assert!(matches!(read_message(&mut stream, &codec), Ok(Message::EndOfGame(_))));
+        assert_eq!(server.join().unwrap().0.len(), 1);
+    }
+
+    #[test]
+    fn is_game_server_timeout() {
+        let config = GameConfig { player_count: 1, rounds: 2, challenge_timeout: Duration::from_millis(100), ..GameConfig::default() };
+        let (address, server, events) = logged_server(config);
+        let sleepy = bot(address, "sleepy", "sleepy", Behaviour::Sleep);
+        let leader_board = server.join().unwrap();
+        sleepy.join().unwrap();
+        assert_eq!(events.lock().unwrap()[1..], ["sleepy timed out", "no active player left after 1 rounds"]);
+
+        let sleepy = &leader_board.0[0];
+        assert!(!sleepy.is_active);
+        assert_eq!((sleepy.steps, sleepy.score), (1, -1));
+    }
+
+    #[test]
+    fn is_game_server_unreachable_target() {
+        let config = GameConfig { player_count: 1, rounds: 1, generators: hash_cash_complexity(4..=4), ..GameConfig::default() };
+        let (address, server) = server(config);
+        let lonely = bot(address, "lonely", "nobody", Behaviour::Solve).join().unwrap();
+        server.join().unwrap();
+
+        let chain = &summaries(&lonely)[0].chain;
+        assert!(matches!(chain[0].value, ChallengeValue::Ok { .. }));
+        assert_eq!(chain[1].name, "nobody");
+        assert_eq!(chain[1].value, ChallengeValue::Unreachable);
+    }
+}
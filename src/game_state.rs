@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::protocol::{ChallengeValue, Message, PublicLeaderBoard, PublicPlayer, RoundSummary, SubscribeError, SubscribeResult};
+
+/// Where our subscription stands
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Registration {
+    #[default]
+    Unsubscribed,
+    /// `Subscribe` was sent, its result has not arrived yet
+    Pending(String),
+    Registered(String),
+    Refused(String, SubscribeError),
+}
+
+/// What a player did in the chains so far, summed over the `RoundSummary` messages
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerRecord {
+    pub challenges: u32,
+    pub ok: u32,
+    pub bad_results: u32,
+    pub timeouts: u32,
+    pub unreachable: u32,
+    pub used_time: f64,
+}
+
+/// Everything the client learnt about the game, fed with every message exchanged with the server
+#[derive(Debug, Clone, Default)]
+pub struct GameState {
+    version: Option<i32>,
+    registration: Registration,
+    leader_board: PublicLeaderBoard,
+    leader_boards: usize,
+    challenges: usize,
+    rounds: Vec<RoundSummary>,
+    records: HashMap<String, PlayerRecord>,
+    score_history: Vec<i32>,
+    is_over: bool,
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        GameState::default()
+    }
+
+    /// Records `message`; `Subscribe` is the only client message it needs, to know who we are
+    pub fn observe(&mut self, message: &Message) {
+        match message {
+            Message::Welcome(welcome) => self.version = Some(welcome.version),
+            Message::Subscribe(subscribe) => self.registration = Registration::Pending(subscribe.name.clone()),
+            Message::SubscribeResult(result) => {
+                if let Registration::Pending(name) = &self.registration {
+                    self.registration = match result {
+                        SubscribeResult::Ok => Registration::Registered(name.clone()),
+                        SubscribeResult::Err(err) => Registration::Refused(name.clone(), err.clone()),
+                    };
+                }
+            }
+            Message::PublicLeaderBoard(board) => {
+                self.leader_boards += 1;
+                self.update_leader_board(board);
+            }
+            Message::Challenge(_) => self.challenges += 1,
+            Message::RoundSummary(summary) => self.record_round(summary),
+            Message::EndOfGame(end) => {
+                self.update_leader_board(&end.leader_board);
+                self.is_over = true;
+            }
+            Message::Hello | Message::ChallengeResult(_) | Message::Unknown(_) => {}
+        }
+    }
+
+    fn update_leader_board(&mut self, board: &PublicLeaderBoard) {
+        self.leader_board = board.clone();
+        if let Some(score) = self.me().map(|player| player.score) {
+            self.score_history.push(score);
+        }
+    }
+
+    fn record_round(&mut self, summary: &RoundSummary) {
+        for result in &summary.chain {
+            let record = self.records.entry(result.name.clone()).or_default();
+            record.challenges += 1;
+            match &result.value {
+                ChallengeValue::Ok { used_time, .. } => {
+                    record.ok += 1;
+                    record.used_time += used_time;
+                }
+                ChallengeValue::BadResult { used_time, .. } => {
+                    record.bad_results += 1;
+                    record.used_time += used_time;
+                }
+                ChallengeValue::Timeout => record.timeouts += 1,
+                ChallengeValue::Unreachable => record.unreachable += 1,
+            }
+        }
+        self.rounds.push(summary.clone());
+    }
+
+    /// Protocol version announced by the server's `Welcome`
+    pub fn version(&self) -> Option<i32> {
+        self.version
+    }
+
+    pub fn registration(&self) -> &Registration {
+        &self.registration
+    }
+
+    /// Our name once the server accepted it
+    pub fn my_name(&self) -> Option<&str> {
+        match &self.registration {
+            Registration::Registered(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Latest leader board, the final one after `EndOfGame`
+    pub fn leader_board(&self) -> &PublicLeaderBoard {
+        &self.leader_board
+    }
+
+    /// Our entry of the latest leader board
+    pub fn me(&self) -> Option<&PublicPlayer> {
+        let name = self.my_name()?;
+        self.leader_board.0.iter().find(|player| player.name == name)
+    }
+
+    /// Our rank on the latest leader board, 1 being the best score
+    pub fn rank(&self) -> Option<usize> {
+        let score = self.me()?.score;
+        Some(1 + self.leader_board.0.iter().filter(|player| player.score > score).count())
+    }
+
+    /// Rounds started so far, each one opening with a leader board
+    pub fn round_count(&self) -> usize {
+        self.leader_boards
+    }
+
+    /// Challenges we received so far
+    pub fn challenge_count(&self) -> usize {
+        self.challenges
+    }
+
+    /// Summaries of the finished rounds, oldest first
+    pub fn rounds(&self) -> &[RoundSummary] {
+        &self.rounds
+    }
+
+    /// Record of `name` over every finished round, `None` when it never received a challenge
+    pub fn record(&self, name: &str) -> Option<&PlayerRecord> {
+        self.records.get(name)
+    }
+
+    /// Our score on each leader board received, the final one included
+    pub fn score_history(&self) -> &[i32] {
+        &self.score_history
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_over
+    }
+
+    /// Human readable summary of the game, for the end of a game
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+        let name = match &self.registration {
+            Registration::Unsubscribed => "?",
+            Registration::Pending(name) | Registration::Registered(name) | Registration::Refused(name, _) => name,
+        };
+        let _ = writeln!(report, "{name}: {} rounds, {} challenges received", self.round_count(), self.challenge_count());
+        if let (Some(me), Some(rank)) = (self.me(), self.rank()) {
+            let _ = writeln!(report, "score {} (rank {rank}/{})", me.score, self.leader_board.0.len());
+        }
+        if let Some(record) = self.record(name) {
+            let _ = writeln!(
+                report,
+                "{} solved, {} wrong, {} timed out, {:.3}s spent",
+                record.ok, record.bad_results, record.timeouts, record.used_time
+            );
+        }
+        let history: Vec<String> = self.score_history.iter().map(i32::to_string).collect();
+        let _ = writeln!(report, "score history: [{}]", history.join(", "));
+        for player in &self.leader_board.0 {
+            let _ = writeln!(report, "  {:>4} {}{}", player.score, player.name, if player.is_active { "" } else { " (inactive)" });
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests_game_state {
+    use super::*;
+    use crate::protocol::{EndOfGame, ReportedChallengeResult, Subscribe, Welcome};
+
+    fn player(name: &str, score: i32) -> PublicPlayer {
+        PublicPlayer { name: name.to_string(), stream_id: String::new(), score, steps: 0, is_active: true, total_used_time: 0.0 }
+    }
+
+    fn result(name: &str, value: ChallengeValue) -> ReportedChallengeResult {
+        ReportedChallengeResult { name: name.to_string(), value }
+    }
+
+    fn registered(name: &str) -> GameState {
+        let mut state = GameState::new();
+        state.observe(&Message::Welcome(Welcome { version: 1 }));
+        state.observe(&Message::Subscribe(Subscribe { name: name.to_string() }));
+        state.observe(&Message::SubscribeResult(SubscribeResult::Ok));
+        state
+    }
+
+    #[test]
+    fn is_game_state_registration() {
+        let mut state = GameState::new();
+        assert_eq!(state.registration(), &Registration::Unsubscribed);
+        state.observe(&Message::Subscribe(Subscribe { name: "me".to_string() }));
+        assert_eq!(state.registration(), &Registration::Pending("me".to_string()));
+        assert_eq!(state.my_name(), None);
+        state.observe(&Message::SubscribeResult(SubscribeResult::Err(SubscribeError::AlreadyRegistered)));
+        assert_eq!(state.registration(), &Registration::Refused("me".to_string(), SubscribeError::AlreadyRegistered));
+
+        let state = registered("me");
+        assert_eq!(state.version(), Some(1));
+        assert_eq!(state.my_name(), Some("me"));
+    }
+
+    #[test]
+    fn is_game_state_rounds_recorded() {
+        let mut state = registered("me");
+        state.observe(&Message::PublicLeaderBoard(PublicLeaderBoard(vec![player("me", 0), player("bob", 0)])));
+        state.observe(&Message::RoundSummary(RoundSummary {
+            challenge: "MD5HashCash".to_string(),
+            chain: vec![
+                result("me", ChallengeValue::Ok { used_time: 0.5, next_target: "bob".to_string() }),
+                result("bob", ChallengeValue::BadResult { used_time: 0.25, next_target: "me".to_string() }),
+            ],
+        }));
+        state.observe(&Message::PublicLeaderBoard(PublicLeaderBoard(vec![player("me", 1), player("bob", -1)])));
+        state.observe(&Message::RoundSummary(RoundSummary {
+            challenge: "MonstrousMaze".to_string(),
+            chain: vec![result("me", ChallengeValue::Timeout)],
+        }));
+        state.observe(&Message::EndOfGame(EndOfGame { leader_board: PublicLeaderBoard(vec![player("bob", 0), player("me", -1)]) }));
+
+        assert!(state.is_over());
+        assert_eq!(state.round_count(), 2);
+        assert_eq!(state.rounds().len(), 2);
+        assert_eq!(state.score_history(), [0, 1, -1]);
+        assert_eq!(state.rank(), Some(2));
+        let me = PlayerRecord { challenges: 2, ok: 1, timeouts: 1, used_time: 0.5, ..PlayerRecord::default() };
+        assert_eq!(state.record("me"), Some(&me));
+        assert_eq!(state.record("bob").map(|record| record.bad_results), Some(1));
+        assert_eq!(state.record("ann"), None);
+        assert!(state.report().contains("score history: [0, 1, -1]"), "{}", state.report());
+    }
+}
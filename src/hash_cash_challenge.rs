@@ -1,115 +1,391 @@
-use std::io::Bytes;
-use std::ops::Deref;
-use std::thread;
 use rand::Rng;
-use md5;
-use rand::rngs::ThreadRng;
-use challenge_trait::ChallengeTrait;
-pub (crate) mod challenge_trait;
-use std::str;
-use md5::Digest;
+use crate::challenge_trait::ChallengeTrait;
+use hash_algorithm::{HashAlgorithm, Md5, Sha1, Sha256};
+use md5_lanes::LANES;
+use seed_search::{SeedRange, SeedSearchReport};
+pub mod generator;
+pub mod hash_algorithm;
+pub mod md5_lanes;
+pub mod seed_search;
+use crate::solve_control::{Progress, SolveLimits, Solved};
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{fmt, str, thread};
 use serde::{Serialize, Deserialize};
 
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MD5HashCashInput {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashCashInput {
     pub complexity: u32,
     pub message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MD5HashCashOutput {
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HashCashOutput {
     pub seed: u64,
     pub hashcode: String,
 }
 
-pub struct HashCash {
-    pub input: MD5HashCashInput
+pub type MD5HashCashInput = HashCashInput;
+pub type MD5HashCashOutput = HashCashOutput;
+pub type SHA1HashCashInput = HashCashInput;
+pub type SHA1HashCashOutput = HashCashOutput;
+pub type SHA256HashCashInput = HashCashInput;
+pub type SHA256HashCashOutput = HashCashOutput;
+
+#[derive(Debug)]
+pub enum HashCashError {
+    /// More leading zeros requested than the digest has bits
+    ComplexityTooHigh { complexity: u32, max: u32 },
 }
 
-impl HashCash {
-    fn get_leading_zeros(byte_arrays: &[u8]) -> u32{
-        let mut leading_zeros: u32 = 0;
-        let mut current_leading_zeros: u32 = 0;
-        for i in 0..byte_arrays.len() {
-            current_leading_zeros = byte_arrays[i].leading_zeros();
-            leading_zeros += current_leading_zeros;
-            if current_leading_zeros < 8 { return leading_zeros; }
+impl fmt::Display for HashCashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashCashError::ComplexityTooHigh { complexity, max } => write!(f, "complexity {complexity} is above the {max} bits of the digest"),
         }
-        return leading_zeros;
     }
+}
+
+/// Proof of work challenge, the same solver whatever the hash `H`
+pub struct HashCash<H: HashAlgorithm> {
+    pub input: HashCashInput,
+    /// Number of worker threads searching for a seed
+    pub threads: usize,
+    /// Seeds to search, a random starting point when `None`
+    pub seed_range: Option<SeedRange>,
+    algorithm: PhantomData<H>,
+}
+
+pub type MD5HashCash = HashCash<Md5>;
+pub type SHA1HashCash = HashCash<Sha1>;
+pub type SHA256HashCash = HashCash<Sha256>;
 
-    fn digest(seed: &u64, message: &String) -> Digest {
-        let mut hexa_seed = format!("{:01$X}", seed, 16);
-        md5::compute((hexa_seed.to_string() + message).as_bytes())
+impl<H: HashAlgorithm> HashCash<H> {
+    /// Seeds claimed at once by a worker
+    const SEED_CHUNK: u64 = 1 << 14;
+    /// How often the solving thread checks the workers, and reports their progress
+    const POLL_INTERVAL: Duration = Duration::from_millis(1);
+    const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+    fn available_threads() -> usize {
+        thread::available_parallelism().map_or(1, |threads| threads.get())
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Searches `range` in order so that the same range always gives the same seed
+    pub fn with_seed_range(mut self, seed_range: SeedRange) -> Self {
+        self.seed_range = Some(seed_range);
+        self
+    }
+
+    /// Searches `seed_range`, or from a random seed when it is not set, until a seed is found
+    /// or `limits` stop it, reporting the seeds tried to `progress`
+    pub fn solve_with_report(&self, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> SeedSearchReport {
+        let range = self.seed_range.unwrap_or_else(|| SeedRange::starting_at(rand::thread_rng().gen_range(0..u64::MAX / 2)));
+        self.search_seeds(range, None, limits, progress)
+    }
+
+    /// Finds the lowest seed of `range` meeting the complexity, trying at most about
+    /// `max_seeds` seeds (rounded up to whole chunks).
+    ///
+    /// Workers claim `SEED_CHUNK` seeds at a time in increasing order and keep searching
+    /// the chunks below a hit, so the result does not depend on the number of threads and
+    /// `remaining` can be handed to a later search to resume exactly where this one stopped.
+    /// When `limits` stop the search, the chunks left unfinished are part of `remaining`
+    /// and a seed found above one of them may not be the lowest.
+    pub fn search_seeds(&self, range: SeedRange, max_seeds: Option<u64>, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> SeedSearchReport {
+        let mut chunk_count = range.len().div_ceil(Self::SEED_CHUNK);
+        if let Some(max_seeds) = max_seeds {
+            chunk_count = chunk_count.min(max_seeds.div_ceil(Self::SEED_CHUNK));
+        }
+        let chunk_start = |chunk: u64| range.start.saturating_add(chunk.saturating_mul(Self::SEED_CHUNK)).min(range.end);
+        let next_chunk = AtomicU64::new(0);
+        let best_chunk = AtomicU64::new(u64::MAX);
+        let first_unfinished_chunk = AtomicU64::new(u64::MAX);
+        let seeds_tried = AtomicU64::new(0);
+        let best: Mutex<Option<HashCashOutput>> = Mutex::new(None);
+
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.max(1)).map(|_| scope.spawn(|| {
+                let mut batch = H::batch(&self.input.message);
+                loop {
+                    let chunk = next_chunk.fetch_add(1, Ordering::Relaxed);
+                    if chunk >= chunk_count || chunk >= best_chunk.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let (start, end) = (chunk_start(chunk), chunk_start(chunk + 1));
+                    let (tried, output) = self.search_chunk(&mut batch, start, end, || best_chunk.load(Ordering::Relaxed) < chunk || limits.should_stop());
+                    seeds_tried.fetch_add(tried, Ordering::Relaxed);
+                    if let Some(output) = output {
+                        let mut best = best.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                        if chunk < best_chunk.load(Ordering::Relaxed) {
+                            best_chunk.store(chunk, Ordering::Relaxed);
+                            *best = Some(output);
+                        }
+                    } else if tried < end - start && chunk < best_chunk.load(Ordering::Relaxed) {
+                        first_unfinished_chunk.fetch_min(chunk, Ordering::Relaxed);
+                        break;
+                    }
+                }
+            })).collect();
+
+            let mut last_report = Instant::now();
+            while !workers.iter().all(|worker| worker.is_finished()) {
+                thread::sleep(Self::POLL_INTERVAL);
+                if last_report.elapsed() >= Self::PROGRESS_INTERVAL {
+                    progress(Progress::Attempts(seeds_tried.load(Ordering::Relaxed)));
+                    last_report = Instant::now();
+                }
+            }
+        });
+
+        let output = best.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let seeds_tried = seeds_tried.into_inner();
+        progress(Progress::Attempts(seeds_tried));
+        let first_unfinished_chunk = first_unfinished_chunk.into_inner();
+        let resume_at = match &output {
+            Some(output) => chunk_start(first_unfinished_chunk).min(output.seed + 1),
+            None => chunk_start(first_unfinished_chunk.min(chunk_count)),
+        };
+        let stopped = if first_unfinished_chunk == u64::MAX { None } else { limits.stop_reason() };
+        SeedSearchReport { output, seeds_tried, remaining: SeedRange::new(resume_at, range.end), stopped }
+    }
+
+    /// Tests the seeds `start..end` in order, `LANES` at a time, until one meets the
+    /// complexity or `abandon` says a lower chunk already has one.
+    fn search_chunk(&self, batch: &mut H::Batch, start: u64, end: u64, abandon: impl Fn() -> bool) -> (u64, Option<HashCashOutput>) {
+        let mut tried = 0;
+        let mut seed = start;
+        while seed < end && !abandon() {
+            for (lane, zeros) in H::leading_zeros(batch, seed).iter().enumerate() {
+                let lane_seed = seed.saturating_add(lane as u64);
+                if lane_seed >= end {
+                    break;
+                }
+                tried += 1;
+                if *zeros >= self.input.complexity {
+                    let hashcode = hash_algorithm::hashcode(&H::digest(lane_seed, &self.input.message));
+                    return (tried, Some(HashCashOutput { seed: lane_seed, hashcode }));
+                }
+            }
+            seed = seed.saturating_add(LANES as u64);
+        }
+        (tried, None)
     }
 }
 
-impl ChallengeTrait for HashCash {
-    type Input = MD5HashCashInput;
-    type Output = MD5HashCashOutput;
+impl<H: HashAlgorithm> ChallengeTrait for HashCash<H> {
+    type Input = HashCashInput;
+    type Output = HashCashOutput;
+    type Error = HashCashError;
 
     fn name() -> String {
-        "hashCash".to_string()
+        H::CHALLENGE_NAME.to_string()
     }
 
-    fn new(input: Self::Input) -> Self {
-        return HashCash { input };
+    fn new(input: Self::Input) -> Result<Self, Self::Error> {
+        if input.complexity > H::DIGEST_BITS {
+            return Err(HashCashError::ComplexityTooHigh { complexity: input.complexity, max: H::DIGEST_BITS });
+        }
+        Ok(HashCash { input, threads: Self::available_threads(), seed_range: None, algorithm: PhantomData })
     }
 
     fn solve(&self) -> Self::Output {
-        let mut output: MD5HashCashOutput = MD5HashCashOutput { seed: 0, hashcode: "".to_string() };
-        let mut found = false;
-        while !found {
-            let seed = rand::thread_rng().gen::<u64>();
-            let digest = HashCash::digest(&seed, &self.input.message);
-            output = MD5HashCashOutput { seed, hashcode: format!("{:X}", digest) };
-            found = HashCash::verify(self, &output)
-        } {}
-        output
+        self.solve_with_report(&SolveLimits::default(), &mut |_| {}).output.unwrap_or_default()
+    }
+
+    fn solve_within(&self, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Solved<Self::Output> {
+        self.solve_with_report(limits, progress).into_solved()
     }
 
     fn verify(&self, answer: &Self::Output) -> bool {
-        let digest = HashCash::digest(&answer.seed, &self.input.message);
-        let zeros= HashCash::get_leading_zeros(digest.as_slice());
-        return zeros >= self.input.complexity && answer.hashcode == format!("{:X}", digest);
+        let digest = H::digest(answer.seed, &self.input.message);
+        let zeros = hash_algorithm::leading_zeros(&digest);
+        zeros >= self.input.complexity && answer.hashcode == hash_algorithm::hashcode(&digest)
     }
 }
 
 #[cfg(test)]
 mod tests_hash_cash {
     use super::*;
+    use hash_algorithm::{hashcode, leading_zeros};
+    use md5_lanes::Md5Lanes;
+    use crate::solve_control::{CancellationToken, StopReason};
 
     #[test]
     fn is_hash_cash_name() {
-        assert_eq!(HashCash::name(), String::from("hashCash"));
+        assert_eq!(MD5HashCash::name(), String::from("hashCash"));
     }
 
     #[test]
     fn is_hash_cash_new() {
-        let new_has_cash = HashCash::new(MD5HashCashInput{complexity: 9, message: "hello".to_string()});
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 9, message: "hello".to_string()}).unwrap();
         assert_eq!(new_has_cash.input.message, String::from("hello"));
         assert_eq!(new_has_cash.input.complexity, 9);
     }
 
+    #[test]
+    fn is_hash_cash_new_too_complex() {
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 129, message: "hello".to_string()});
+        assert!(matches!(new_has_cash, Err(HashCashError::ComplexityTooHigh { complexity: 129, max: 128 })));
+    }
+
     #[test]
     fn is_hash_cash_get_leading_zeros() {
         let bytes_array= [0, 80, 139, 24, 242, 10, 109, 203, 203, 90, 106, 97, 186, 192, 120, 168];
-        let zeros = HashCash::get_leading_zeros(&bytes_array);
+        let zeros = leading_zeros(&bytes_array);
         assert_eq!(zeros, 9);
     }
 
     #[test]
     fn is_hash_cash_digest() {
-        let digest = HashCash::digest(&844, &String::from("hello"));
-        let hash = format!("{:X}", digest);
+        let hash = hashcode(&Md5::digest(844, "hello"));
         assert_eq!(hash, "00441745D9BDF8E5D3C7872AC9DBB2C3");
     }
 
     #[test]
     fn is_hash_cash_verify() {
-        let new_has_cash = HashCash::new(MD5HashCashInput{complexity: 9, message: String::from("hello")});
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 9, message: String::from("hello")}).unwrap();
         let output = MD5HashCashOutput{seed: 844 ,hashcode: String::from("00441745D9BDF8E5D3C7872AC9DBB2C3")};
-        assert_eq!(HashCash::verify(&new_has_cash,&output), true);
+        assert!(MD5HashCash::verify(&new_has_cash,&output));
+    }
+
+    #[test]
+    fn is_hash_cash_solve_multi_threaded() {
+        for threads in [1, 4] {
+            let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 12, message: String::from("hello")}).unwrap().with_threads(threads);
+            assert_eq!(new_has_cash.threads, threads);
+            let output = new_has_cash.solve();
+            assert!(new_has_cash.verify(&output));
+        }
+    }
+
+    #[test]
+    fn is_hash_cash_sha_solved() {
+        let sha1 = SHA1HashCash::new(SHA1HashCashInput{complexity: 10, message: String::from("hello")}).unwrap().with_threads(2);
+        assert_eq!(SHA1HashCash::name(), "sha1HashCash");
+        assert!(sha1.verify(&sha1.solve()));
+
+        let sha256 = SHA256HashCash::new(SHA256HashCashInput{complexity: 10, message: String::from("hello")}).unwrap().with_threads(2);
+        let output = sha256.solve();
+        assert!(sha256.verify(&output));
+        assert_eq!(output.hashcode.len(), 64);
+        assert!(!MD5HashCash::new(MD5HashCashInput{complexity: 10, message: String::from("hello")}).unwrap().verify(&output));
+    }
+
+    #[test]
+    fn is_hash_cash_new_too_complex_for_sha256() {
+        assert!(SHA256HashCash::new(SHA256HashCashInput{complexity: 256, message: String::from("hello")}).is_ok());
+        let new_has_cash = SHA256HashCash::new(SHA256HashCashInput{complexity: 257, message: String::from("hello")});
+        assert!(matches!(new_has_cash, Err(HashCashError::ComplexityTooHigh { complexity: 257, max: 256 })));
+    }
+
+    #[test]
+    fn is_hash_cash_search_deterministic() {
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 10, message: String::from("hello")}).unwrap();
+        let range = SeedRange::new(1_000, 100_000);
+        let single = new_has_cash.with_threads(1).search_seeds(range, None, &SolveLimits::default(), &mut |_| {});
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 10, message: String::from("hello")}).unwrap();
+        let multi = new_has_cash.with_threads(4).search_seeds(range, None, &SolveLimits::default(), &mut |_| {});
+
+        let seed = single.output.unwrap().seed;
+        assert_eq!(multi.output.unwrap().seed, seed);
+        assert_eq!(single.seeds_tried, seed - 1_000 + 1);
+        assert_eq!(single.remaining, SeedRange::new(seed + 1, 100_000));
+        assert!((1_000..seed).all(|seed| leading_zeros(&Md5::digest(seed, "hello")) < 10));
+    }
+
+    #[test]
+    fn is_hash_cash_search_resumable() {
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 14, message: String::from("hello")}).unwrap().with_threads(2);
+        let full = new_has_cash.search_seeds(SeedRange::starting_at(0), None, &SolveLimits::default(), &mut |_| {}).output.unwrap();
+
+        let first = new_has_cash.search_seeds(SeedRange::starting_at(0), Some(1), &SolveLimits::default(), &mut |_| {});
+        assert!(first.output.is_none() || first.output.as_ref().unwrap().seed == full.seed);
+        let mut remaining = first.remaining;
+        let mut found = first.output;
+        while found.is_none() {
+            let report = new_has_cash.search_seeds(remaining, Some(1), &SolveLimits::default(), &mut |_| {});
+            assert_eq!(report.remaining.start, report.output.as_ref().map_or(remaining.start + MD5HashCash::SEED_CHUNK, |output| output.seed + 1));
+            remaining = report.remaining;
+            found = report.output;
+        }
+        assert_eq!(found.unwrap().seed, full.seed);
+    }
+
+    #[test]
+    fn is_hash_cash_solve_within_deadline() {
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 100, message: String::from("hello")}).unwrap().with_threads(2);
+        let limits = SolveLimits::default().with_timeout(Duration::from_millis(300));
+        let mut attempts = vec![];
+        let report = new_has_cash.search_seeds(SeedRange::starting_at(0), None, &limits, &mut |progress| attempts.push(progress));
+        assert!(report.output.is_none());
+        assert_eq!(report.stopped, Some(StopReason::Deadline));
+        assert!(report.remaining.start <= report.seeds_tried);
+        assert!(attempts.len() > 1);
+        assert_eq!(attempts.last(), Some(&Progress::Attempts(report.seeds_tried)));
+
+        assert_eq!(new_has_cash.solve_within(&limits, &mut |_| {}), Solved::GaveUp(StopReason::Deadline));
+    }
+
+    #[test]
+    fn is_hash_cash_solve_within_cancelled() {
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 12, message: String::from("hello")}).unwrap();
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let limits = SolveLimits::default().with_cancellation(cancellation);
+        let report = new_has_cash.search_seeds(SeedRange::new(10, 1_000_000), None, &limits, &mut |_| {});
+        assert_eq!(report.stopped, Some(StopReason::Cancelled));
+        assert_eq!(report.remaining, SeedRange::new(10, 1_000_000));
+
+        let solved = new_has_cash.solve_within(&SolveLimits::default(), &mut |_| {});
+        assert!(new_has_cash.verify(&solved.answer().unwrap()));
+    }
+
+    #[test]
+    fn is_hash_cash_search_exhausted() {
+        let new_has_cash = MD5HashCash::new(MD5HashCashInput{complexity: 64, message: String::from("hello")}).unwrap();
+        let report = new_has_cash.search_seeds(SeedRange::new(0, 1_000), None, &SolveLimits::default(), &mut |_| {});
+        assert!(report.output.is_none());
+        assert_eq!(report.seeds_tried, 1_000);
+        assert!(report.remaining.is_empty());
+    }
+
+    /// Compares the per-seed `md5::compute` loop with the batched one:
+    /// `cargo test --release hash_rate -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn is_hash_cash_hash_rate() {
+        let duration = std::time::Duration::from_secs(2);
+        let message = "The quick brown fox jumps over the lazy dog";
+
+        let start = std::time::Instant::now();
+        let mut hashes: u64 = 0;
+        while start.elapsed() < duration {
+            let digest = Md5::digest(hashes, message);
+            let output = MD5HashCashOutput { seed: hashes, hashcode: hashcode(&digest) };
+            assert!(!output.hashcode.is_empty());
+            hashes += 1;
+        }
+        println!("per-seed md5::compute: {:.0} hashes/s", hashes as f64 / start.elapsed().as_secs_f64());
+
+        let mut lanes = Md5Lanes::new(message);
+        let start = std::time::Instant::now();
+        let mut hashes: u64 = 0;
+        while start.elapsed() < duration {
+            for lane in 0..LANES {
+                lanes.set_seed(lane, hashes + lane as u64);
+            }
+            assert!(lanes.compute().iter().all(|state| Md5Lanes::leading_zeros(state) < 128));
+            hashes += LANES as u64;
+        }
+        println!("Md5Lanes ({LANES} lanes): {:.0} hashes/s", hashes as f64 / start.elapsed().as_secs_f64());
     }
 }
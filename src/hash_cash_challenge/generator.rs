@@ -0,0 +1,79 @@
+use std::ops::RangeInclusive;
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+
+use super::hash_algorithm::HashAlgorithm;
+use super::{HashCash, HashCashInput};
+use crate::challenge_trait::ChallengeGenerator;
+
+const MESSAGE_LENGTH: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct HashCashSettings {
+    /// Message to prefix with the seed, a random alphanumeric one when `None`
+    pub message: Option<String>,
+    /// Capped to the digest size of the hash
+    pub complexity: RangeInclusive<u32>,
+}
+
+impl Default for HashCashSettings {
+    fn default() -> Self {
+        HashCashSettings { message: None, complexity: 8..=12 }
+    }
+}
+
+impl<H: HashAlgorithm> ChallengeGenerator for HashCash<H> {
+    type Settings = HashCashSettings;
+
+    fn generate<R: Rng + ?Sized>(settings: &Self::Settings, rng: &mut R) -> Self::Input {
+        let (low, high) = (*settings.complexity.start(), *settings.complexity.end());
+        let high = high.min(H::DIGEST_BITS);
+        let complexity = rng.gen_range(low.min(high)..=high);
+        let message = match &settings.message {
+            Some(message) => message.clone(),
+            None => rng.sample_iter(Alphanumeric).take(MESSAGE_LENGTH).map(char::from).collect(),
+        };
+        HashCashInput { complexity, message }
+    }
+}
+
+#[cfg(test)]
+mod tests_hash_cash_generator {
+    use super::*;
+    use super::super::hash_algorithm::{Md5, Sha1, Sha256};
+    use crate::challenge_trait::ChallengeTrait;
+    use crate::hash_cash_challenge::{MD5HashCash, SHA1HashCash, SHA256HashCash};
+
+    fn is_generated_solvable<H: HashAlgorithm>() {
+        let settings = HashCashSettings { message: None, complexity: 4..=10 };
+        for seed in 0..10 {
+            let input = HashCash::<H>::generate_seeded(&settings, seed);
+            assert!(settings.complexity.contains(&input.complexity));
+            assert_eq!(input.message.len(), MESSAGE_LENGTH);
+            let hash_cash = HashCash::<H>::new(input).unwrap().with_threads(1);
+            assert!(hash_cash.verify(&hash_cash.solve()));
+        }
+    }
+
+    #[test]
+    fn is_hash_cash_generated_solvable() {
+        is_generated_solvable::<Md5>();
+        is_generated_solvable::<Sha1>();
+        is_generated_solvable::<Sha256>();
+    }
+
+    #[test]
+    fn is_hash_cash_generated_seeded() {
+        let settings = HashCashSettings::default();
+        let generate = |seed| MD5HashCash::generate_seeded(&settings, seed);
+        assert_eq!(generate(7).message, generate(7).message);
+        assert_eq!(generate(7).complexity, generate(7).complexity);
+        assert_ne!(generate(7).message, generate(8).message);
+
+        let settings = HashCashSettings { message: Some("hello".to_string()), complexity: 300..=400 };
+        assert_eq!(SHA1HashCash::generate_seeded(&settings, 0).complexity, 160);
+        assert_eq!(SHA256HashCash::generate_seeded(&settings, 0).complexity, 256);
+        assert_eq!(SHA256HashCash::generate_seeded(&settings, 0).message, "hello");
+    }
+}
@@ -0,0 +1,168 @@
+use sha2::Digest;
+
+use super::md5_lanes::{Md5Lanes, LANES};
+
+/// Length of the seed written in front of the message
+pub(crate) const SEED_DIGITS: usize = 16;
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Hash function of a proof of work challenge: the digest of `<16 hex digits of the seed><message>`
+/// must start with `complexity` zero bits.
+pub trait HashAlgorithm: Sync {
+    /// Name of the challenge using this hash
+    const CHALLENGE_NAME: &'static str;
+    const DIGEST_BITS: u32;
+    /// Reusable state hashing `LANES` seeds of the same message at once
+    type Batch;
+
+    fn batch(message: &str) -> Self::Batch;
+    /// Leading zero bits of the digests of the seeds `first_seed..first_seed + LANES`
+    fn leading_zeros(batch: &mut Self::Batch, first_seed: u64) -> [u32; LANES];
+    fn digest(seed: u64, message: &str) -> Vec<u8>;
+}
+
+pub struct Md5;
+
+impl HashAlgorithm for Md5 {
+    const CHALLENGE_NAME: &'static str = "hashCash";
+    const DIGEST_BITS: u32 = 128;
+    type Batch = Md5Lanes;
+
+    fn batch(message: &str) -> Self::Batch {
+        Md5Lanes::new(message)
+    }
+
+    fn leading_zeros(batch: &mut Self::Batch, first_seed: u64) -> [u32; LANES] {
+        for lane in 0..LANES {
+            batch.set_seed(lane, first_seed.saturating_add(lane as u64));
+        }
+        batch.compute().map(|state| Md5Lanes::leading_zeros(&state))
+    }
+
+    fn digest(seed: u64, message: &str) -> Vec<u8> {
+        md5::compute(seeded_message(seed, message)).to_vec()
+    }
+}
+
+pub struct Sha1;
+
+impl HashAlgorithm for Sha1 {
+    const CHALLENGE_NAME: &'static str = "sha1HashCash";
+    const DIGEST_BITS: u32 = 160;
+    type Batch = DigestBatch;
+
+    fn batch(message: &str) -> Self::Batch {
+        DigestBatch::new(message)
+    }
+
+    fn leading_zeros(batch: &mut Self::Batch, first_seed: u64) -> [u32; LANES] {
+        batch.leading_zeros::<sha1::Sha1>(first_seed)
+    }
+
+    fn digest(seed: u64, message: &str) -> Vec<u8> {
+        sha1::Sha1::digest(seeded_message(seed, message)).to_vec()
+    }
+}
+
+pub struct Sha256;
+
+impl HashAlgorithm for Sha256 {
+    const CHALLENGE_NAME: &'static str = "sha256HashCash";
+    const DIGEST_BITS: u32 = 256;
+    type Batch = DigestBatch;
+
+    fn batch(message: &str) -> Self::Batch {
+        DigestBatch::new(message)
+    }
+
+    fn leading_zeros(batch: &mut Self::Batch, first_seed: u64) -> [u32; LANES] {
+        batch.leading_zeros::<sha2::Sha256>(first_seed)
+    }
+
+    fn digest(seed: u64, message: &str) -> Vec<u8> {
+        sha2::Sha256::digest(seeded_message(seed, message)).to_vec()
+    }
+}
+
+/// Hashes the seeds one after the other with a `digest` hash, reusing the message buffer
+pub struct DigestBatch {
+    buffer: Vec<u8>,
+}
+
+impl DigestBatch {
+    fn new(message: &str) -> Self {
+        DigestBatch { buffer: seeded_message(0, message) }
+    }
+
+    fn leading_zeros<D: Digest>(&mut self, first_seed: u64) -> [u32; LANES] {
+        std::array::from_fn(|lane| {
+            write_seed(&mut self.buffer[..SEED_DIGITS], first_seed.saturating_add(lane as u64));
+            leading_zeros(&D::digest(&self.buffer))
+        })
+    }
+}
+
+/// Writes `seed` as `SEED_DIGITS` upper case hex digits into `digits`
+pub(crate) fn write_seed(digits: &mut [u8], seed: u64) {
+    for (index, digit) in digits.iter_mut().enumerate() {
+        let nibble = (seed >> (60 - 4 * index)) & 0xF;
+        *digit = HEX_DIGITS[nibble as usize];
+    }
+}
+
+fn seeded_message(seed: u64, message: &str) -> Vec<u8> {
+    let mut buffer = vec![0; SEED_DIGITS];
+    write_seed(&mut buffer, seed);
+    buffer.extend_from_slice(message.as_bytes());
+    buffer
+}
+
+pub fn leading_zeros(bytes: &[u8]) -> u32 {
+    let mut leading_zeros = 0;
+    for byte in bytes {
+        let current_leading_zeros = byte.leading_zeros();
+        leading_zeros += current_leading_zeros;
+        if current_leading_zeros < 8 { return leading_zeros; }
+    }
+    leading_zeros
+}
+
+/// Digest written the way the server expects it, in upper case hex
+pub fn hashcode(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{byte:02X}")).collect()
+}
+
+#[cfg(test)]
+mod tests_hash_algorithm {
+    use super::*;
+
+    fn is_batch_matching_digest<H: HashAlgorithm>() {
+        let mut batch = H::batch("hello");
+        let zeros = H::leading_zeros(&mut batch, 840);
+        for (lane, zeros) in zeros.iter().enumerate() {
+            let digest = H::digest(840 + lane as u64, "hello");
+            assert_eq!(digest.len() as u32 * 8, H::DIGEST_BITS);
+            assert_eq!(*zeros, leading_zeros(&digest));
+        }
+    }
+
+    #[test]
+    fn is_hash_algorithm_batch_matching_digest() {
+        is_batch_matching_digest::<Md5>();
+        is_batch_matching_digest::<Sha1>();
+        is_batch_matching_digest::<Sha256>();
+    }
+
+    #[test]
+    fn is_hash_algorithm_digest() {
+        assert_eq!(hashcode(&Md5::digest(844, "hello")), "00441745D9BDF8E5D3C7872AC9DBB2C3");
+        assert_eq!(hashcode(&Sha1::digest(0, "")), hashcode(&sha1::Sha1::digest(b"0000000000000000")));
+        assert_eq!(hashcode(&Sha256::digest(255, "abc")), hashcode(&sha2::Sha256::digest(b"00000000000000FFabc")));
+    }
+
+    #[test]
+    fn is_hash_algorithm_leading_zeros() {
+        assert_eq!(leading_zeros(&[0, 80, 139, 24]), 9);
+        assert_eq!(leading_zeros(&[0, 0]), 16);
+    }
+}
@@ -0,0 +1,172 @@
+use super::hash_algorithm::{write_seed, SEED_DIGITS};
+
+/// Number of messages hashed together by `Md5Lanes::compute`
+pub const LANES: usize = 8;
+
+const BLOCK_SIZE: usize = 64;
+
+const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+const SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+    5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+    4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+    6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+const CONSTANTS: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+/// MD5 of `LANES` messages of the form `<16 hex digits of the seed><message>` at once.
+///
+/// Every lane owns an already padded copy of the message: trying new seeds only rewrites
+/// the first 16 bytes, and the rounds run on `[u32; LANES]` so that the compiler can keep
+/// all the lanes in vector registers.
+pub struct Md5Lanes {
+    buffers: [Vec<u8>; LANES],
+}
+
+impl Md5Lanes {
+    pub fn new(message: &str) -> Self {
+        let length = SEED_DIGITS + message.len();
+        let mut buffer = vec![b'0'; SEED_DIGITS];
+        buffer.extend_from_slice(message.as_bytes());
+        buffer.push(0x80);
+        while buffer.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+            buffer.push(0);
+        }
+        buffer.extend_from_slice(&((length as u64).wrapping_mul(8)).to_le_bytes());
+        Md5Lanes { buffers: std::array::from_fn(|_| buffer.clone()) }
+    }
+
+    /// Writes `seed` as 16 upper case hex digits at the start of the lane's message
+    pub fn set_seed(&mut self, lane: usize, seed: u64) {
+        write_seed(&mut self.buffers[lane][..SEED_DIGITS], seed);
+    }
+
+    /// Raw MD5 state of every lane, `[a, b, c, d]` as the digest stores them in little endian
+    pub fn compute(&self) -> [[u32; 4]; LANES] {
+        let mut state = [[0u32; LANES]; 4];
+        for (word, initial) in state.iter_mut().zip(INITIAL_STATE) {
+            *word = [initial; LANES];
+        }
+
+        for block in 0..self.buffers[0].len() / BLOCK_SIZE {
+            let mut words = [[0u32; LANES]; 16];
+            for (lane, buffer) in self.buffers.iter().enumerate() {
+                let block = &buffer[block * BLOCK_SIZE..(block + 1) * BLOCK_SIZE];
+                for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+                    word[lane] = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                }
+            }
+            Md5Lanes::compress(&mut state, &words);
+        }
+
+        std::array::from_fn(|lane| [state[0][lane], state[1][lane], state[2][lane], state[3][lane]])
+    }
+
+    fn compress(state: &mut [[u32; LANES]; 4], words: &[[u32; LANES]; 16]) {
+        let [mut a, mut b, mut c, mut d] = *state;
+        for (step, word) in words.iter().enumerate() {
+            Md5Lanes::step::<0>(&mut a, &mut b, &mut c, &mut d, word, step);
+        }
+        for step in 16..32 {
+            Md5Lanes::step::<1>(&mut a, &mut b, &mut c, &mut d, &words[(5 * step + 1) % 16], step);
+        }
+        for step in 32..48 {
+            Md5Lanes::step::<2>(&mut a, &mut b, &mut c, &mut d, &words[(3 * step + 5) % 16], step);
+        }
+        for step in 48..64 {
+            Md5Lanes::step::<3>(&mut a, &mut b, &mut c, &mut d, &words[(7 * step) % 16], step);
+        }
+        for lane in 0..LANES {
+            state[0][lane] = state[0][lane].wrapping_add(a[lane]);
+            state[1][lane] = state[1][lane].wrapping_add(b[lane]);
+            state[2][lane] = state[2][lane].wrapping_add(c[lane]);
+            state[3][lane] = state[3][lane].wrapping_add(d[lane]);
+        }
+    }
+
+    /// One of the 64 MD5 steps on every lane, `ROUND` selecting the mixing function
+    #[inline(always)]
+    fn step<const ROUND: usize>(a: &mut [u32; LANES], b: &mut [u32; LANES], c: &mut [u32; LANES], d: &mut [u32; LANES], word: &[u32; LANES], step: usize) {
+        let mut next_b = [0u32; LANES];
+        for lane in 0..LANES {
+            let mixed = match ROUND {
+                0 => (b[lane] & c[lane]) | (!b[lane] & d[lane]),
+                1 => (d[lane] & b[lane]) | (!d[lane] & c[lane]),
+                2 => b[lane] ^ c[lane] ^ d[lane],
+                _ => c[lane] ^ (b[lane] | !d[lane]),
+            };
+            let rotated = mixed
+                .wrapping_add(a[lane])
+                .wrapping_add(CONSTANTS[step])
+                .wrapping_add(word[lane])
+                .rotate_left(SHIFTS[step]);
+            next_b[lane] = b[lane].wrapping_add(rotated);
+        }
+        *a = *d;
+        *d = *c;
+        *c = *b;
+        *b = next_b;
+    }
+
+    /// Leading zero bits of the digest made of `state` written in little endian
+    pub fn leading_zeros(state: &[u32; 4]) -> u32 {
+        let mut leading_zeros = 0;
+        for word in state {
+            let zeros = word.swap_bytes().leading_zeros();
+            leading_zeros += zeros;
+            if zeros < 32 {
+                break;
+            }
+        }
+        leading_zeros
+    }
+}
+
+#[cfg(test)]
+mod tests_md5_lanes {
+    use super::*;
+
+    fn to_bytes(state: &[u32; 4]) -> [u8; 16] {
+        let mut bytes = [0; 16];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn is_md5_lanes_matching_md5() {
+        for message in ["", "hello", &"long message spanning several blocks ".repeat(5)] {
+            let mut lanes = Md5Lanes::new(message);
+            for lane in 0..LANES {
+                lanes.set_seed(lane, 844 + 1_000_003 * lane as u64);
+            }
+            for (lane, state) in lanes.compute().iter().enumerate() {
+                let expected = md5::compute(format!("{:016X}{}", 844 + 1_000_003 * lane as u64, message));
+                assert_eq!(to_bytes(state), expected.0);
+            }
+        }
+    }
+
+    #[test]
+    fn is_md5_lanes_leading_zeros() {
+        let mut lanes = Md5Lanes::new("hello");
+        lanes.set_seed(0, 844);
+        let state = lanes.compute()[0];
+        assert_eq!(to_bytes(&state)[..2], [0x00, 0x44]);
+        assert_eq!(Md5Lanes::leading_zeros(&state), 9);
+        assert_eq!(Md5Lanes::leading_zeros(&[0, 0, 0, 0]), 128);
+        assert_eq!(Md5Lanes::leading_zeros(&[0, 0x8000_0000, 0, 0]), 56);
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Serialize, Deserialize};
+
+use super::HashCashOutput;
+use crate::solve_control::{Solved, StopReason};
+
+/// Half-open range of seeds `start..end`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl SeedRange {
+    pub fn new(start: u64, end: u64) -> Self {
+        SeedRange { start, end: end.max(start) }
+    }
+
+    /// Every seed from `start` on
+    pub fn starting_at(start: u64) -> Self {
+        SeedRange::new(start, u64::MAX)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Cuts the range into `parts` consecutive ranges, e.g. one per process
+    pub fn split(&self, parts: u64) -> Vec<SeedRange> {
+        let parts = parts.max(1);
+        let size = self.len() / parts;
+        let extra = self.len() % parts;
+        let mut start = self.start;
+        (0..parts).map(|part| {
+            let end = start + size + u64::from(part < extra);
+            let range = SeedRange::new(start, end);
+            start = end;
+            range
+        }).collect()
+    }
+}
+
+impl fmt::Display for SeedRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl FromStr for SeedRange {
+    type Err = String;
+
+    /// Parses `start..end`, `start..` or a single `start`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |bound: &str| bound.trim().parse::<u64>().map_err(|err| format!("invalid seed {bound:?}: {err}"));
+        match s.split_once("..") {
+            Some((start, "")) => Ok(SeedRange::starting_at(parse(start)?)),
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if end < start {
+                    return Err(format!("seed range {s:?} ends before it starts"));
+                }
+                Ok(SeedRange::new(start, end))
+            }
+            None => Ok(SeedRange::starting_at(parse(s)?)),
+        }
+    }
+}
+
+/// Outcome of a seed search, enough to resume it later
+#[derive(Debug)]
+pub struct SeedSearchReport {
+    /// Lowest matching seed of the searched part of the range
+    pub output: Option<HashCashOutput>,
+    pub seeds_tried: u64,
+    /// What is left to search: after the found seed, or after the last searched seed
+    pub remaining: SeedRange,
+    /// Why the search stopped before finishing its part of the range
+    pub stopped: Option<StopReason>,
+}
+
+impl SeedSearchReport {
+    pub fn into_solved(self) -> Solved<HashCashOutput> {
+        match self.output {
+            Some(output) => Solved::Answer(output),
+            None => Solved::GaveUp(self.stopped.unwrap_or(StopReason::Exhausted)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_seed_search {
+    use super::*;
+
+    #[test]
+    fn is_seed_range_parsed() {
+        assert_eq!("10..20".parse(), Ok(SeedRange::new(10, 20)));
+        assert_eq!("10..".parse(), Ok(SeedRange::starting_at(10)));
+        assert_eq!("42".parse(), Ok(SeedRange::starting_at(42)));
+        assert!("20..10".parse::<SeedRange>().is_err());
+        assert!("ten".parse::<SeedRange>().is_err());
+        assert_eq!(SeedRange::new(10, 20).to_string(), "10..20");
+    }
+
+    #[test]
+    fn is_seed_range_split() {
+        let parts = SeedRange::new(0, 10).split(3);
+        assert_eq!(parts, vec![SeedRange::new(0, 4), SeedRange::new(4, 7), SeedRange::new(7, 10)]);
+        assert_eq!(parts.iter().map(SeedRange::len).sum::<u64>(), 10);
+    }
+}
@@ -0,0 +1,15 @@
+//! Protocol types and challenge solvers shared by the client and the server
+
+pub mod challenge_registry;
+pub mod challenge_trait;
+pub mod error;
+pub mod frame_codec;
+pub mod game_server;
+pub mod game_state;
+pub mod hash_cash_challenge;
+pub mod monstrous_maze_challenge;
+pub mod name_policy;
+pub mod protocol;
+pub mod recover_secret_challenge;
+pub mod solve_control;
+pub mod target_strategy;
@@ -1,237 +1,236 @@
-use challenge_trait::ChallengeTrait;
-pub (crate) mod challenge_trait;
-
+use crate::challenge_trait::ChallengeTrait;
+use grid::{Cell, Direction, Grid};
+pub mod generator;
+pub mod grid;
+
+use crate::solve_control::{Progress, SolveLimits, Solved, StopReason};
+use std::collections::VecDeque;
+use std::fmt;
 use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonstrousMazeInput {
     pub grid: String,
     pub endurance: u8,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MonstrousMazeOutput {
     pub path: String
 }
 
-pub struct MonstrousMaze {
-    pub input: MonstrousMazeInput,
-    pub maze: Vec<String>,
-    pub startPoint: (u64, u64),
-    pub endPoint: (u64, u64),
+/// `(row, column)` of a cell, counted in characters
+pub type Coordinates = (u64, u64);
+
+#[derive(Debug, PartialEq)]
+pub enum MazeError {
+    Empty,
+    MissingStart,
+    MissingEnd,
+    /// Every `(row, column)` where a start was found
+    MultipleStarts(Vec<Coordinates>),
+    /// Every `(row, column)` where an end was found
+    MultipleEnds(Vec<Coordinates>),
+    RaggedRow { row: usize, width: usize, expected: usize },
 }
 
-struct Grid {
-    grid: Vec<String>,
-    start: (u64, u64),
-    end: (u64, u64),
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::Empty => write!(f, "the grid is empty"),
+            MazeError::MissingStart => write!(f, "the grid has no start '{}'", Cell::START_CHARACTER),
+            MazeError::MissingEnd => write!(f, "the grid has no end '{}'", Cell::END_CHARACTER),
+            MazeError::MultipleStarts(starts) => write!(f, "the grid has {} starts '{}' at {:?}", starts.len(), Cell::START_CHARACTER, starts),
+            MazeError::MultipleEnds(ends) => write!(f, "the grid has {} ends '{}' at {:?}", ends.len(), Cell::END_CHARACTER, ends),
+            MazeError::RaggedRow { row, width, expected } => write!(f, "row {row} is {width} cells wide, expected {expected}"),
+        }
+    }
 }
 
-struct GridPossibleSolution {
-    current_coordinates: (i64, i64),
-    path_taken: String,
-    visited_coordinates: Vec<(i64, i64)>,
-    encoutered_monster: i64,
-    success: bool,
+#[derive(Debug, PartialEq)]
+pub enum PathViolation {
+    UnknownDirection(char),
+    OutOfGrid,
+    Wall(char),
+    /// The endurance is 0: even a path meeting no monster has not strictly fewer monsters
+    NoEndurance,
+    /// The step meets the monster that exhausts the endurance
+    TooManyMonsters { encountered: u8, endurance: u8 },
+    EndNotReached,
 }
 
-impl MonstrousMaze {
-    const START_CHARACTER: char = 'Y';
-    const END_CHARACTER: char = 'X';
-    const MONSTER_CHARACTER: char = 'M';
-    const FREE_WAY_CHARACTER: char = ' ';
-
-    fn stringified_maze_to(mazeString: String) -> (Vec<String>, (u64, u64), (u64, u64)) {
-        let maze: Vec<String> = mazeString.lines().map(str::to_string).collect();
-        let mut startPoint: (u64, u64) = (0, 0);
-        let mut endPoint: (u64, u64) = (0, 0);
-
-        for (y, row) in maze.iter().enumerate() {
-            let startX = row.find(MonstrousMaze::START_CHARACTER) ;
-            let endX = row.find(MonstrousMaze::END_CHARACTER) ;
-            if startX.is_some() { startPoint = ( y as u64, startX.unwrap() as u64 -2)}
-            if endX.is_some() { endPoint = (y as u64, endX.unwrap() as u64 -2 )}
-        }
-
-        (maze, startPoint, endPoint)
-    }
+/// First step of a path that breaks the maze rules
+#[derive(Debug, PartialEq)]
+pub struct IllegalStep {
+    /// Index of the offending character in the path (the path length for `EndNotReached`)
+    pub step: usize,
+    /// Position before the offending step
+    pub coordinates: Coordinates,
+    pub violation: PathViolation,
+}
 
-    fn is_coordinates_in_grid( coordinate: (i64,i64), grid: &Grid) -> bool{
-        if coordinate.0 > -1 && coordinate.1 > -1 && coordinate.0 < grid.grid.len() as i64 && coordinate.1 < grid.grid[coordinate.0 as usize].len() as i64{
-            let current_line: String = grid.grid[coordinate.0 as usize].clone();
-            let current_char: char = current_line.chars().nth(coordinate.1 as usize).unwrap() as char;
-            if current_char == MonstrousMaze::START_CHARACTER || current_char == MonstrousMaze::END_CHARACTER || current_char == MonstrousMaze::MONSTER_CHARACTER || current_char == MonstrousMaze::FREE_WAY_CHARACTER {
-                return true
-            }
-            return false;
+impl fmt::Display for IllegalStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {} from {:?}: ", self.step, self.coordinates)?;
+        match &self.violation {
+            PathViolation::UnknownDirection(c) => write!(f, "unknown direction {c:?}"),
+            PathViolation::OutOfGrid => write!(f, "leaves the grid"),
+            PathViolation::Wall(c) => write!(f, "walks into the wall {c:?}"),
+            PathViolation::NoEndurance => write!(f, "no maze can be crossed with an endurance of 0"),
+            PathViolation::TooManyMonsters { encountered, endurance } => write!(f, "meets monster number {encountered} with an endurance of {endurance}"),
+            PathViolation::EndNotReached => write!(f, "the path stops before the end"),
         }
-        return false
     }
+}
+
+pub struct MonstrousMaze {
+    pub input: MonstrousMazeInput,
+    pub maze: Grid,
+    pub start_point: Coordinates,
+    pub end_point: Coordinates,
+}
 
-    fn is_coordinates_monster( coordinate: (i64,i64), grid: &Grid) -> bool{
-        let current_line: String = grid.grid[coordinate.0 as usize].clone();
-        let current_char: char = current_line.chars().nth(coordinate.1 as usize).unwrap() as char;
-        return current_char == MonstrousMaze::MONSTER_CHARACTER;
+/// A search state: a cell plus the number of monsters met to reach it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SearchState {
+    coordinates: Coordinates,
+    encountered_monsters: u8,
+}
+
+impl MonstrousMaze {
+    const CHECK_INTERVAL: u64 = 4_096;
+
+    fn state_index(&self, state: SearchState) -> usize {
+        let cell = state.coordinates.0 as usize * self.maze.width() + state.coordinates.1 as usize;
+        cell * self.input.endurance as usize + state.encountered_monsters as usize
     }
 
-    fn find_paths(grid: &Grid, mut grid_possible_solution: GridPossibleSolution) -> Vec<GridPossibleSolution> {
-        if grid_possible_solution.visited_coordinates.contains(&grid_possible_solution.current_coordinates) {
-            return vec![];
+    /// Breadth-first search over `(cell, monsters met)` states.
+    ///
+    /// Every move costs one step, so the first state reaching the end gives the
+    /// shortest path that meets strictly fewer monsters than `endurance`. `limits` are checked,
+    /// and progress reported, every `CHECK_INTERVAL` explored states.
+    fn find_shortest_path(&self, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Result<String, StopReason> {
+        if self.input.endurance == 0 {
+            return Err(StopReason::Exhausted);
         }
-        grid_possible_solution.visited_coordinates.push(grid_possible_solution.current_coordinates);
-
-        let mut paths: Vec<GridPossibleSolution> = vec![];
-
-        //println!("Current coordinates : {:?}", grid_possible_solution.current_coordinates);
-        //println!("Current path taken : {:?}", grid_possible_solution.path_taken);
-        let current_line: String = grid.grid[grid_possible_solution.current_coordinates.0 as usize].clone();
-        let current_char: char = current_line.chars().nth(grid_possible_solution.current_coordinates.1 as usize).unwrap() as char;
-        //println!("Current char: {}", current_char);
-
-        return if current_char == MonstrousMaze::START_CHARACTER ||
-                  current_char == MonstrousMaze::END_CHARACTER ||
-                  current_char == MonstrousMaze::MONSTER_CHARACTER ||
-                  current_char == MonstrousMaze::FREE_WAY_CHARACTER {
-            if current_char == MonstrousMaze::END_CHARACTER {
-                grid_possible_solution.success = true;
-                paths.push(grid_possible_solution);
-                return paths;
-            }
 
-            let mut all_paths: Vec<GridPossibleSolution> = vec![];
+        let state_count = self.maze.width() * self.maze.height() * self.input.endurance as usize;
+        let mut came_from: Vec<Option<(SearchState, Direction)>> = vec![None; state_count];
+        let mut visited = vec![false; state_count];
+        let mut queue = VecDeque::new();
 
-            let right_direction = '>';
-            let right_coordinates = (grid_possible_solution.current_coordinates.0, grid_possible_solution.current_coordinates.1 + 1);
-            if MonstrousMaze::is_coordinates_in_grid(right_coordinates, grid) {
-                let mut monster = 0;
-                if MonstrousMaze::is_coordinates_monster(right_coordinates, grid) {
-                    monster += 1;
-                }
-                //println!("Going : right");
-                let mut visited_coordinates = grid_possible_solution.visited_coordinates.clone();
-                let right_grid_possible_solution = GridPossibleSolution {
-                    current_coordinates: right_coordinates,
-                    path_taken: format!("{}{}", grid_possible_solution.path_taken.clone(), right_direction),
-                    visited_coordinates,
-                    encoutered_monster: grid_possible_solution.encoutered_monster + monster,
-                    success: false,
-                };
-                all_paths.append(&mut MonstrousMaze::find_paths(&grid, right_grid_possible_solution));
-            }
+        let start = SearchState { coordinates: self.start_point, encountered_monsters: 0 };
+        visited[self.state_index(start)] = true;
+        queue.push_back(start);
 
-            let top_direction = '^';
-            let top_coordinates = (grid_possible_solution.current_coordinates.0 - 1, grid_possible_solution.current_coordinates.1);
-            if MonstrousMaze::is_coordinates_in_grid(top_coordinates, grid) {
-                let mut monster = 0;
-                if MonstrousMaze::is_coordinates_monster(top_coordinates, grid) {
-                    monster += 1;
+        let mut explored: u64 = 0;
+        while let Some(state) = queue.pop_front() {
+            if state.coordinates == self.end_point {
+                progress(Progress::ExploredStates(explored));
+                return Ok(self.rebuild_path(&came_from, state));
+            }
+            explored += 1;
+            if explored.is_multiple_of(MonstrousMaze::CHECK_INTERVAL) {
+                progress(Progress::ExploredStates(explored));
+                if let Some(reason) = limits.stop_reason() {
+                    return Err(reason);
                 }
-                //println!("Going : top");
-                let mut visited_coordinates = grid_possible_solution.visited_coordinates.clone();
-                let top_grid_possible_solution = GridPossibleSolution {
-                    current_coordinates: top_coordinates,
-                    path_taken: format!("{}{}", grid_possible_solution.path_taken.clone(), top_direction),
-                    visited_coordinates,
-                    encoutered_monster: grid_possible_solution.encoutered_monster + monster,
-                    success: false,
-                };
-                all_paths.append(&mut MonstrousMaze::find_paths(&grid, top_grid_possible_solution));
             }
-
-            let left_direction = '<';
-            let left_coordinates = (grid_possible_solution.current_coordinates.0, grid_possible_solution.current_coordinates.1 - 1);
-            if MonstrousMaze::is_coordinates_in_grid(left_coordinates, grid) {
-                let mut monster = 0;
-                if MonstrousMaze::is_coordinates_monster(left_coordinates, grid) {
-                    monster += 1;
+            for direction in Direction::ALL {
+                let Some(next_coordinates) = self.maze.neighbour(state.coordinates, direction) else { continue };
+                let encountered_monsters = state.encountered_monsters + u8::from(self.maze.is_monster(next_coordinates));
+                if encountered_monsters >= self.input.endurance {
+                    continue;
+                }
+                let next = SearchState { coordinates: next_coordinates, encountered_monsters };
+                let index = self.state_index(next);
+                if !visited[index] {
+                    visited[index] = true;
+                    came_from[index] = Some((state, direction));
+                    queue.push_back(next);
                 }
-                //println!("Going : left");
-                let mut visited_coordinates = grid_possible_solution.visited_coordinates.clone();
-                let left_grid_possible_solution = GridPossibleSolution {
-                    current_coordinates: left_coordinates,
-                    path_taken: format!("{}{}", grid_possible_solution.path_taken.clone(), left_direction),
-                    visited_coordinates,
-                    encoutered_monster: grid_possible_solution.encoutered_monster + monster,
-                    success: false,
-                };
-                all_paths.append(&mut MonstrousMaze::find_paths(&grid, left_grid_possible_solution));
             }
+        }
+        progress(Progress::ExploredStates(explored));
+        Err(StopReason::Exhausted)
+    }
 
+    /// Replays `path` cell by cell and reports the first step breaking a rule
+    pub fn check_path(&self, path: &str) -> Result<(), IllegalStep> {
+        let mut current = self.start_point;
+        let mut encountered_monsters: u8 = 0;
+        let illegal = |step, coordinates, violation| Err(IllegalStep { step, coordinates, violation });
+        if self.input.endurance == 0 {
+            return illegal(0, current, PathViolation::NoEndurance);
+        }
 
-            let bottom_direction = 'v';
-            let bottom_coordinates = (grid_possible_solution.current_coordinates.0 + 1, grid_possible_solution.current_coordinates.1);
-            if MonstrousMaze::is_coordinates_in_grid(bottom_coordinates, grid) {
-                let mut monster = 0;
-                if MonstrousMaze::is_coordinates_monster(bottom_coordinates, grid) {
-                    monster += 1;
+        for (step, c) in path.chars().enumerate() {
+            let Some(direction) = Direction::from_char(c) else {
+                return illegal(step, current, PathViolation::UnknownDirection(c));
+            };
+            let Some(next) = direction.step(current) else {
+                return illegal(step, current, PathViolation::OutOfGrid);
+            };
+            match self.maze.get(next) {
+                None => return illegal(step, current, PathViolation::OutOfGrid),
+                Some(Cell::Wall(wall)) => return illegal(step, current, PathViolation::Wall(wall)),
+                Some(Cell::Monster) => {
+                    encountered_monsters = encountered_monsters.saturating_add(1);
+                    if encountered_monsters >= self.input.endurance {
+                        let violation = PathViolation::TooManyMonsters { encountered: encountered_monsters, endurance: self.input.endurance };
+                        return illegal(step, current, violation);
+                    }
                 }
-                //println!("Going : bottom");
-                let mut visited_coordinates = grid_possible_solution.visited_coordinates.clone();
-                let bottom_grid_possible_solution = GridPossibleSolution {
-                    current_coordinates: bottom_coordinates,
-                    path_taken: format!("{}{}", grid_possible_solution.path_taken.clone(), bottom_direction),
-                    visited_coordinates,
-                    encoutered_monster: grid_possible_solution.encoutered_monster + monster,
-                    success: false,
-                };
-                all_paths.append(&mut MonstrousMaze::find_paths(&grid, bottom_grid_possible_solution));
+                Some(_) => {}
             }
+            current = next;
+        }
 
-            //println!("\n======\n");
-            all_paths
-        } else {
-            paths
+        if current != self.end_point {
+            return illegal(path.chars().count(), current, PathViolation::EndNotReached);
         }
+        Ok(())
     }
 
+    fn rebuild_path(&self, came_from: &[Option<(SearchState, Direction)>], end: SearchState) -> String {
+        let mut directions = vec![];
+        let mut current = end;
+        while let Some((previous, direction)) = came_from[self.state_index(current)] {
+            directions.push(direction.to_char());
+            current = previous;
+        }
+        directions.iter().rev().collect()
+    }
 }
 
 impl ChallengeTrait for MonstrousMaze {
     type Input = MonstrousMazeInput;
     type Output = MonstrousMazeOutput;
+    type Error = MazeError;
 
     fn name() -> String {
         "monstrousMaze".to_string()
     }
 
-    fn new(input: Self::Input) -> Self {
-        let (maze, startPoint, endPoint) = MonstrousMaze::stringified_maze_to("│Y M X│".to_string());
-        return MonstrousMaze { input, maze, startPoint, endPoint };
+    fn new(input: Self::Input) -> Result<Self, Self::Error> {
+        let (maze, start_point, end_point) = Grid::parse(&input.grid)?;
+        Ok(MonstrousMaze { input, maze, start_point, end_point })
     }
 
     fn solve(&self) -> Self::Output {
-        let start = self.startPoint.clone();
-        let results = MonstrousMaze::find_paths(
-            &Grid {
-            grid: self.maze.clone(),
-            start: self.startPoint.clone(),
-            end: self.endPoint.clone()
-        },
-            GridPossibleSolution {
-            current_coordinates: ( start.0 as i64, start.1 as i64) ,
-            path_taken: "".to_string(),
-            visited_coordinates: vec![],
-            encoutered_monster: 0,
-            success: false
-        } );
-        for result in results {
-            let res = MonstrousMazeOutput{path: result.path_taken.clone()};
-            if self.verify(&res)  && result.encoutered_monster < self.input.endurance.into() { return res; }
+        MonstrousMazeOutput { path: self.find_shortest_path(&SolveLimits::default(), &mut |_| {}).unwrap_or_default() }
+    }
+
+    fn solve_within(&self, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Solved<Self::Output> {
+        match self.find_shortest_path(limits, progress) {
+            Ok(path) => Solved::Answer(MonstrousMazeOutput { path }),
+            Err(reason) => Solved::GaveUp(reason),
         }
-        MonstrousMazeOutput{path: "".to_string()}
     }
 
     fn verify(&self, answer: &Self::Output) -> bool {
-        let chars: Vec<char> = answer.path.chars().collect();
-        let mut startX = self.startPoint.1.clone();
-        let mut startY = self.startPoint.0.clone();
-        for c in chars {
-            if c == '>' { startX+=1 }
-            else if c == '^' { startY-=1}
-            else if c == '<' { startX-=1 }
-            else if c == 'v' { startY+=1 }
-        }
-        return startY == self.endPoint.0 && startX == self.endPoint.1
+        self.check_path(&answer.path).is_ok()
     }
 }
 
@@ -239,6 +238,7 @@ impl ChallengeTrait for MonstrousMaze {
 #[cfg(test)]
 mod tests_monstrous_maze {
     use super::*;
+    use crate::solve_control::CancellationToken;
 
     #[test]
     fn is_monstrous_maze_name() {
@@ -247,31 +247,144 @@ mod tests_monstrous_maze {
 
     #[test]
     fn is_monstrous_maze_new() {
-        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: "│Y M X│".to_string()});
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: "│Y M X│".to_string()}).unwrap();
         assert_eq!(new_maze.input.endurance, 2);
         assert_eq!(new_maze.input.grid, "│Y M X│");
     }
 
     #[test]
     fn is_monstrous_maze_in_grid() {
-        let subgrid = "│Y M X│".lines().map(str::to_string).collect();
-        let grid = Grid{grid: subgrid ,start:(0,1) ,end: (0,5) };
-        let is_in_grid = MonstrousMaze::is_coordinates_in_grid( (0,2), &grid);
-        assert_eq!(is_in_grid, true);
+        let (grid, _, _) = Grid::parse("│Y M X│").unwrap();
+        assert!(grid.is_walkable((0,2)));
+        assert!(!grid.is_walkable((0,6)));
+        assert!(!grid.is_walkable((0,7)));
     }
 
     #[test]
     fn is_monstrous_maze_monster() {
-        let subgrid = "│Y M X│".lines().map(str::to_string).collect();
-        let grid = Grid{grid: subgrid ,start:(0,1) ,end: (0,5) };
-        let is_monster_coordinate = MonstrousMaze::is_coordinates_monster( (0,3), &grid);
-        assert_eq!(is_monster_coordinate, true);
+        let (grid, _, _) = Grid::parse("│Y M X│").unwrap();
+        let is_monster_coordinate = grid.is_monster((0,3));
+        assert!(is_monster_coordinate);
     }
 
     #[test]
     fn is_monstrous_maze_verify() {
-        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: "│Y M X│".to_string()});
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: "│Y M X│".to_string()}).unwrap();
         let output = MonstrousMazeOutput{path: ">>>>".to_string()};
-        assert_eq!(MonstrousMaze::verify(&new_maze,&output), true);
+        assert!(MonstrousMaze::verify(&new_maze,&output));
+    }
+
+    #[test]
+    fn is_monstrous_maze_new_multiline() {
+        let grid = "┌───┐\n│ M │\n│Y│X│\n└───┘";
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: grid.to_string()}).unwrap();
+        assert_eq!(new_maze.maze.height(), 4);
+        assert_eq!(new_maze.start_point, (2, 1));
+        assert_eq!(new_maze.end_point, (2, 3));
+        assert_eq!(new_maze.solve().path, "^>>v");
+    }
+
+    #[test]
+    fn is_monstrous_maze_new_invalid() {
+        let new_maze = |grid: &str| MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: grid.to_string()}).err();
+        assert_eq!(new_maze(""), Some(MazeError::Empty));
+        assert_eq!(new_maze("│ M X│"), Some(MazeError::MissingStart));
+        assert_eq!(new_maze("│Y M  │"), Some(MazeError::MissingEnd));
+        assert_eq!(new_maze("│Y Y X│"), Some(MazeError::MultipleStarts(vec![(0, 1), (0, 3)])));
+        assert_eq!(new_maze("│Y X X│"), Some(MazeError::MultipleEnds(vec![(0, 3), (0, 5)])));
+        assert_eq!(new_maze("│Y M X│\n│   │"), Some(MazeError::RaggedRow { row: 1, width: 5, expected: 7 }));
+    }
+
+    #[test]
+    fn is_monstrous_maze_solve_avoiding_monsters() {
+        let grid = "┌─────┐\n│Y M X│\n│     │\n└─────┘";
+        let solve = |endurance| MonstrousMaze::new(MonstrousMazeInput{endurance, grid: grid.to_string()}).unwrap().solve().path;
+        assert_eq!(solve(2), ">>>>");
+        assert_eq!(solve(1).len(), 6);
+        assert!(!solve(1).starts_with(">>"));
+        assert_eq!(solve(0), "");
+    }
+
+    #[test]
+    fn is_monstrous_maze_solve_unreachable() {
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 1, grid: "│Y│M│X│".to_string()}).unwrap();
+        assert_eq!(new_maze.solve().path, "");
+    }
+
+    #[test]
+    fn is_monstrous_maze_solve_large_open_grid() {
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 3, grid: open_grid(50)}).unwrap();
+        let output = new_maze.solve();
+        assert_eq!(output.path.len(), 98);
+        assert!(new_maze.verify(&output));
+    }
+
+    #[test]
+    fn is_monstrous_maze_solve_within_limits() {
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 3, grid: open_grid(50)}).unwrap();
+        let mut explored = vec![];
+        let solved = new_maze.solve_within(&SolveLimits::default(), &mut |progress| explored.push(progress));
+        assert_eq!(solved.answer().map(|output| output.path.len()), Some(98));
+        assert!(explored.len() > 1);
+
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+        let solved = new_maze.solve_within(&SolveLimits::default().with_cancellation(cancellation), &mut |_| {});
+        assert_eq!(solved, Solved::GaveUp(StopReason::Cancelled));
+
+        let unreachable = MonstrousMaze::new(MonstrousMazeInput{endurance: 1, grid: "│Y│M│X│".to_string()}).unwrap();
+        assert_eq!(unreachable.solve_within(&SolveLimits::default(), &mut |_| {}), Solved::GaveUp(StopReason::Exhausted));
+    }
+
+    /// `size`x`size` free grid from the top left to the bottom right corner with a few monsters
+    fn open_grid(size: usize) -> String {
+        let mut rows = vec![format!("┌{}┐", "─".repeat(size))];
+        for y in 0..size {
+            let mut row: Vec<char> = " ".repeat(size).chars().collect();
+            if y == 0 { row[0] = 'Y' }
+            if y == size - 1 { row[size - 1] = 'X' }
+            if y % 7 == 3 { row[size / 2] = 'M' }
+            rows.push(format!("│{}│", row.iter().collect::<String>()));
+        }
+        rows.push(format!("└{}┘", "─".repeat(size)));
+        rows.join("\n")
+    }
+
+    #[test]
+    fn is_monstrous_maze_check_path() {
+        let grid = "┌───┐\n│ M │\n│Y│X│\n└───┘";
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: grid.to_string()}).unwrap();
+        let check = |path: &str| new_maze.check_path(path).err().map(|illegal| (illegal.step, illegal.coordinates, illegal.violation));
+        assert_eq!(check("^>>v"), None);
+        assert_eq!(check("^>>x"), Some((3, (1, 3), PathViolation::UnknownDirection('x'))));
+        assert_eq!(check(">"), Some((0, (2, 1), PathViolation::Wall('│'))));
+        assert_eq!(check("^^^"), Some((1, (1, 1), PathViolation::Wall('─'))));
+        assert_eq!(check("^>"), Some((2, (1, 2), PathViolation::EndNotReached)));
+
+        let weak_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 1, grid: grid.to_string()}).unwrap();
+        let illegal = weak_maze.check_path("^>>v").unwrap_err();
+        assert_eq!(illegal.violation, PathViolation::TooManyMonsters { encountered: 1, endurance: 1 });
+        assert!(!weak_maze.verify(&MonstrousMazeOutput{path: "^>>v".to_string()}));
+    }
+
+    #[test]
+    fn is_monstrous_maze_no_endurance() {
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 0, grid: "│Y X│".to_string()}).unwrap();
+        assert_eq!(new_maze.solve_within(&SolveLimits::default(), &mut |_| {}), Solved::GaveUp(StopReason::Exhausted));
+        let solved = new_maze.solve();
+        assert_eq!(solved.path, "");
+        assert!(!new_maze.verify(&solved));
+        assert!(!new_maze.verify(&MonstrousMazeOutput{path: ">>".to_string()}));
+        assert_eq!(new_maze.check_path(">>").map_err(|illegal| illegal.violation), Err(PathViolation::NoEndurance));
+    }
+
+    #[test]
+    fn is_monstrous_maze_check_path_out_of_grid() {
+        let new_maze = MonstrousMaze::new(MonstrousMazeInput{endurance: 2, grid: "Y X".to_string()}).unwrap();
+        let check = |path: &str| new_maze.check_path(path).err().map(|illegal| illegal.violation);
+        assert_eq!(check(">>"), None);
+        assert_eq!(check("<"), Some(PathViolation::OutOfGrid));
+        assert_eq!(check("^"), Some(PathViolation::OutOfGrid));
+        assert_eq!(check("v"), Some(PathViolation::OutOfGrid));
     }
 }
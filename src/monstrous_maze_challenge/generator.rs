@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::grid::Cell;
+use super::{Coordinates, MonstrousMaze, MonstrousMazeInput};
+use crate::challenge_trait::ChallengeGenerator;
+
+const INNER_WALL: char = '█';
+
+#[derive(Debug, Clone)]
+pub struct MazeSettings {
+    /// Cells inside the border
+    pub width: usize,
+    pub height: usize,
+    /// Chance for a cell off the guaranteed path to be a wall, from 0 to 1
+    pub wall_density: f64,
+    /// Fewer monsters are placed when the free cells run out
+    pub monsters: usize,
+    /// Raised to 1, since no maze can be crossed with no endurance
+    pub endurance: u8,
+}
+
+impl Default for MazeSettings {
+    fn default() -> Self {
+        MazeSettings { width: 20, height: 10, wall_density: 0.3, monsters: 6, endurance: 2 }
+    }
+}
+
+impl ChallengeGenerator for MonstrousMaze {
+    type Settings = MazeSettings;
+
+    /// Carves a random monotone path from `Y` to `X` first, holding fewer monsters than the
+    /// endurance, so that the maze is always solvable
+    fn generate<R: Rng + ?Sized>(settings: &Self::Settings, rng: &mut R) -> Self::Input {
+        let height = settings.height.max(1);
+        // Room for at least the start and the end
+        let width = if height == 1 { settings.width.max(2) } else { settings.width.max(1) };
+        let endurance = settings.endurance.max(1);
+
+        let random_cell = |rng: &mut R| (rng.gen_range(0..height) as u64, rng.gen_range(0..width) as u64);
+        let start = random_cell(rng);
+        let end = loop {
+            let end = random_cell(rng);
+            if end != start { break end }
+        };
+        let path = carve_path(start, end, rng);
+
+        let wall_density = settings.wall_density.clamp(0.0, 1.0);
+        let mut cells: Vec<Vec<Cell>> = (0..height as u64).map(|row| (0..width as u64).map(|column| {
+            if path.contains(&(row, column)) || !rng.gen_bool(wall_density) { Cell::Free } else { Cell::Wall(INNER_WALL) }
+        }).collect()).collect();
+        cells[start.0 as usize][start.1 as usize] = Cell::Start;
+        cells[end.0 as usize][end.1 as usize] = Cell::End;
+
+        let mut free: Vec<Coordinates> = (0..height as u64)
+            .flat_map(|row| (0..width as u64).map(move |column| (row, column)))
+            .filter(|&(row, column)| cells[row as usize][column as usize] == Cell::Free)
+            .collect();
+        free.shuffle(rng);
+        let mut monsters_on_path = 0;
+        let monsters = free.into_iter().filter(|coordinates| {
+            if !path.contains(coordinates) { return true }
+            monsters_on_path += 1;
+            monsters_on_path < endurance
+        });
+        for (row, column) in monsters.take(settings.monsters) {
+            cells[row as usize][column as usize] = Cell::Monster;
+        }
+
+        MonstrousMazeInput { grid: render(&cells, width), endurance }
+    }
+}
+
+/// Cells of a shortest path from `start` to `end`, taking random turns
+fn carve_path<R: Rng + ?Sized>(start: Coordinates, end: Coordinates, rng: &mut R) -> HashSet<Coordinates> {
+    let mut path = HashSet::from([start]);
+    let mut current = start;
+    while current != end {
+        let vertical = current.0 != end.0 && (current.1 == end.1 || rng.gen_bool(0.5));
+        current = match vertical {
+            true if current.0 < end.0 => (current.0 + 1, current.1),
+            true => (current.0 - 1, current.1),
+            false if current.1 < end.1 => (current.0, current.1 + 1),
+            false => (current.0, current.1 - 1),
+        };
+        path.insert(current);
+    }
+    path
+}
+
+/// The grid as the server sends it, framed by a border
+fn render(cells: &[Vec<Cell>], width: usize) -> String {
+    let mut rows = vec![format!("┌{}┐", "─".repeat(width))];
+    rows.extend(cells.iter().map(|row| format!("│{}│", row.iter().map(|cell| cell.to_char()).collect::<String>())));
+    rows.push(format!("└{}┘", "─".repeat(width)));
+    rows.join("\n")
+}
+
+#[cfg(test)]
+mod tests_maze_generator {
+    use super::*;
+    use crate::challenge_trait::ChallengeTrait;
+
+    fn count(grid: &str, character: char) -> usize {
+        grid.chars().filter(|&c| c == character).count()
+    }
+
+    #[test]
+    fn is_maze_generated_solvable() {
+        let settings = MazeSettings { width: 30, height: 15, wall_density: 0.6, monsters: 40, endurance: 2 };
+        for seed in 0..50 {
+            let input = MonstrousMaze::generate_seeded(&settings, seed);
+            let maze = MonstrousMaze::new(input).unwrap();
+            assert_eq!((maze.maze.width(), maze.maze.height()), (32, 17));
+            assert!(maze.verify(&maze.solve()), "seed {seed}:\n{}", maze.input.grid);
+        }
+    }
+
+    #[test]
+    fn is_maze_generated_settings() {
+        let settings = MazeSettings { width: 8, height: 4, wall_density: 0.0, monsters: 5, endurance: 0 };
+        let input = MonstrousMaze::generate_seeded(&settings, 1);
+        assert_eq!(input.endurance, 1);
+        assert_eq!(count(&input.grid, Cell::MONSTER_CHARACTER), 5);
+        assert_eq!(count(&input.grid, INNER_WALL), 0);
+        assert_eq!(input.grid, MonstrousMaze::generate_seeded(&settings, 1).grid);
+        assert!(MonstrousMaze::new(input).unwrap().solve_within(&Default::default(), &mut |_| {}).answer().is_some());
+
+        let tiny = MonstrousMaze::generate_seeded(&MazeSettings { width: 0, height: 1, wall_density: 0.0, monsters: 10, endurance: 3 }, 1);
+        assert!(tiny.grid == "┌──┐\n│YX│\n└──┘" || tiny.grid == "┌──┐\n│XY│\n└──┘");
+    }
+
+    #[test]
+    fn is_maze_generated_wall_density() {
+        // Fully walled, only the carved path is left open: a shortest path from Y to X
+        let settings = MazeSettings { width: 12, height: 6, wall_density: 1.0, monsters: 0, endurance: 1 };
+        for seed in 0..20 {
+            let maze = MonstrousMaze::new(MonstrousMaze::generate_seeded(&settings, seed)).unwrap();
+            let open = (0..maze.maze.height() as u64)
+                .flat_map(|row| (0..maze.maze.width() as u64).map(move |column| (row, column)))
+                .filter(|&coordinates| maze.maze.is_walkable(coordinates))
+                .count() as u64;
+            let ((start_row, start_column), (end_row, end_column)) = (maze.start_point, maze.end_point);
+            let distance = start_row.abs_diff(end_row) + start_column.abs_diff(end_column);
+            assert_eq!(open, distance + 1, "seed {seed}:\n{}", maze.input.grid);
+            assert_eq!(count(&maze.input.grid, INNER_WALL) as u64, 12 * 6 - open);
+            assert_eq!(maze.solve().path.len() as u64, distance);
+        }
+
+        // About half of the cells are walls, the path being a small part of the grid
+        let settings = MazeSettings { width: 40, height: 20, wall_density: 0.5, monsters: 0, endurance: 1 };
+        for seed in 0..20 {
+            let walls = count(&MonstrousMaze::generate_seeded(&settings, seed).grid, INNER_WALL) as f64;
+            assert!((0.4..0.6).contains(&(walls / (40.0 * 20.0))), "seed {seed}: {walls} walls");
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use super::{Coordinates, MazeError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    /// Anything that cannot be walked on, keeping the glyph the server used
+    Wall(char),
+    Free,
+    Monster,
+    Start,
+    End,
+}
+
+impl Cell {
+    pub const START_CHARACTER: char = 'Y';
+    pub const END_CHARACTER: char = 'X';
+    pub const MONSTER_CHARACTER: char = 'M';
+    pub const FREE_WAY_CHARACTER: char = ' ';
+
+    pub fn from_char(character: char) -> Cell {
+        match character {
+            Cell::START_CHARACTER => Cell::Start,
+            Cell::END_CHARACTER => Cell::End,
+            Cell::MONSTER_CHARACTER => Cell::Monster,
+            Cell::FREE_WAY_CHARACTER => Cell::Free,
+            wall => Cell::Wall(wall),
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Cell::Start => Cell::START_CHARACTER,
+            Cell::End => Cell::END_CHARACTER,
+            Cell::Monster => Cell::MONSTER_CHARACTER,
+            Cell::Free => Cell::FREE_WAY_CHARACTER,
+            Cell::Wall(wall) => wall,
+        }
+    }
+
+    pub fn is_walkable(self) -> bool {
+        !matches!(self, Cell::Wall(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Right,
+    Up,
+    Left,
+    Down,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Right, Direction::Up, Direction::Left, Direction::Down];
+
+    pub fn from_char(character: char) -> Option<Direction> {
+        match character {
+            '>' => Some(Direction::Right),
+            '^' => Some(Direction::Up),
+            '<' => Some(Direction::Left),
+            'v' => Some(Direction::Down),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Direction::Right => '>',
+            Direction::Up => '^',
+            Direction::Left => '<',
+            Direction::Down => 'v',
+        }
+    }
+
+    /// Coordinates one step away, or `None` when the step would go below zero
+    pub fn step(self, (row, column): Coordinates) -> Option<Coordinates> {
+        match self {
+            Direction::Right => Some((row, column.checked_add(1)?)),
+            Direction::Up => Some((row.checked_sub(1)?, column)),
+            Direction::Left => Some((row, column.checked_sub(1)?)),
+            Direction::Down => Some((row.checked_add(1)?, column)),
+        }
+    }
+}
+
+/// A rectangular maze where every character of the server grid is one cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    cells: Vec<Vec<Cell>>,
+    width: usize,
+}
+
+impl Grid {
+    /// Parses the grid, returning it along with its start and end coordinates
+    pub fn parse(maze_string: &str) -> Result<(Grid, Coordinates, Coordinates), MazeError> {
+        let cells: Vec<Vec<Cell>> = maze_string.lines().map(|row| row.chars().map(Cell::from_char).collect()).collect();
+        if cells.is_empty() {
+            return Err(MazeError::Empty);
+        }
+
+        let width = cells[0].len();
+        let mut starts: Vec<Coordinates> = vec![];
+        let mut ends: Vec<Coordinates> = vec![];
+
+        for (y, row) in cells.iter().enumerate() {
+            if row.len() != width {
+                return Err(MazeError::RaggedRow { row: y, width: row.len(), expected: width });
+            }
+            for (x, cell) in row.iter().enumerate() {
+                match cell {
+                    Cell::Start => starts.push((y as u64, x as u64)),
+                    Cell::End => ends.push((y as u64, x as u64)),
+                    _ => {}
+                }
+            }
+        }
+
+        let start_point = match starts[..] {
+            [] => return Err(MazeError::MissingStart),
+            [start] => start,
+            _ => return Err(MazeError::MultipleStarts(starts)),
+        };
+        let end_point = match ends[..] {
+            [] => return Err(MazeError::MissingEnd),
+            [end] => end,
+            _ => return Err(MazeError::MultipleEnds(ends)),
+        };
+
+        Ok((Grid { cells, width }, start_point, end_point))
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The cell at `(row, column)`, or `None` outside of the grid
+    pub fn get(&self, (row, column): Coordinates) -> Option<Cell> {
+        let row = usize::try_from(row).ok()?;
+        let column = usize::try_from(column).ok()?;
+        self.cells.get(row)?.get(column).copied()
+    }
+
+    pub fn is_walkable(&self, coordinates: Coordinates) -> bool {
+        self.get(coordinates).is_some_and(Cell::is_walkable)
+    }
+
+    pub fn is_monster(&self, coordinates: Coordinates) -> bool {
+        self.get(coordinates) == Some(Cell::Monster)
+    }
+
+    /// Walkable neighbour reached by going one step in `direction`
+    pub fn neighbour(&self, coordinates: Coordinates, direction: Direction) -> Option<Coordinates> {
+        direction.step(coordinates).filter(|next| self.is_walkable(*next))
+    }
+
+    /// Draws the grid with every cell walked by `path` (from `start`) replaced by its arrow
+    pub fn render_path(&self, start: Coordinates, path: &str) -> String {
+        let mut canvas: Vec<Vec<char>> = self.cells.iter().map(|row| row.iter().map(|cell| cell.to_char()).collect()).collect();
+        let mut current = start;
+        for direction in path.chars().map_while(Direction::from_char) {
+            let Some(next) = direction.step(current) else { break };
+            let Some(cell) = self.get(next) else { break };
+            if cell == Cell::Free {
+                canvas[next.0 as usize][next.1 as usize] = direction.to_char();
+            }
+            current = next;
+        }
+        canvas.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<String>>().join("\n")
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.cells.iter().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell.to_char())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_grid {
+    use super::*;
+
+    const GRID: &str = "┌───┐\n│ M │\n│Y│X│\n└───┘";
+
+    #[test]
+    fn is_grid_parse_unicode_borders() {
+        let (grid, start, end) = Grid::parse(GRID).unwrap();
+        assert_eq!((grid.height(), grid.width()), (4, 5));
+        assert_eq!(start, (2, 1));
+        assert_eq!(end, (2, 3));
+        assert_eq!(grid.get((0, 4)), Some(Cell::Wall('┐')));
+        assert_eq!(grid.get((1, 2)), Some(Cell::Monster));
+        assert_eq!(grid.get((4, 0)), None);
+    }
+
+    #[test]
+    fn is_grid_parse_ascii_borders() {
+        let (_, start, end) = Grid::parse("+-----+\n|Y M X|\n+-----+").unwrap();
+        assert_eq!(start, (1, 1));
+        assert_eq!(end, (1, 5));
+    }
+
+    #[test]
+    fn is_grid_neighbour() {
+        let (grid, start, _) = Grid::parse(GRID).unwrap();
+        assert_eq!(grid.neighbour(start, Direction::Up), Some((1, 1)));
+        assert_eq!(grid.neighbour(start, Direction::Right), None);
+        assert_eq!(grid.neighbour((0, 0), Direction::Up), None);
+    }
+
+    #[test]
+    fn is_grid_rendering() {
+        let (grid, start, _) = Grid::parse(GRID).unwrap();
+        assert_eq!(grid.to_string(), GRID);
+        assert_eq!(grid.render_path(start, "^>>v"), "┌───┐\n│^M>│\n│Y│X│\n└───┘");
+    }
+}
@@ -0,0 +1,204 @@
+use crate::protocol::SubscribeError;
+
+/// Names of the character sets, as given to `Charset::named`
+pub const CHARSET_NAMES: [&str; 3] = ["alphanumeric", "ascii", "printable"];
+
+/// Characters kept in a sanitized name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// ASCII letters, digits, `-` and `_`
+    Alphanumeric,
+    /// Visible ASCII characters and spaces
+    Ascii,
+    /// Anything but control characters
+    Printable,
+}
+
+impl Charset {
+    pub fn named(name: &str) -> Option<Charset> {
+        match name {
+            "alphanumeric" => Some(Charset::Alphanumeric),
+            "ascii" => Some(Charset::Ascii),
+            "printable" => Some(Charset::Printable),
+            _ => None,
+        }
+    }
+
+    pub fn allows(self, c: char) -> bool {
+        match self {
+            Charset::Alphanumeric => c.is_ascii_alphanumeric() || c == '-' || c == '_',
+            Charset::Ascii => c.is_ascii_graphic() || c == ' ',
+            Charset::Printable => !c.is_control(),
+        }
+    }
+}
+
+/// How a name is changed when the server refuses it: a numbered suffix when it is taken,
+/// sanitizing when it is invalid
+#[derive(Debug, Clone)]
+pub struct NamePolicy {
+    /// Put between the name and its number, `name-2` with `-`
+    pub separator: String,
+    /// Number of the first retry with a taken name
+    pub first_suffix: u32,
+    /// Names tried at most, the first one included
+    pub max_attempts: usize,
+    pub charset: Charset,
+    /// Stands for each character out of `charset`, which are removed when `None`
+    pub replacement: Option<char>,
+    /// Length of a sanitized name in characters, its suffix included
+    pub max_length: usize,
+}
+
+impl Default for NamePolicy {
+    fn default() -> Self {
+        NamePolicy { separator: "-".to_string(), first_suffix: 2, max_attempts: 10, charset: Charset::Alphanumeric, replacement: Some('_'), max_length: 32 }
+    }
+}
+
+impl NamePolicy {
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_charset(mut self, charset: Charset) -> Self {
+        self.charset = charset;
+        self
+    }
+
+    pub fn with_replacement(mut self, replacement: Option<char>) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// `name` with only the characters of the charset, trimmed, and short enough to leave
+    /// room for `suffix`
+    pub fn sanitize(&self, name: &str, suffix: &str) -> String {
+        let kept: String = name
+            .trim()
+            .chars()
+            .filter_map(|c| if self.charset.allows(c) { Some(c) } else { self.replacement })
+            .collect();
+        let room = self.max_length.saturating_sub(suffix.chars().count());
+        let mut sanitized: String = kept.trim().chars().take(room).collect();
+        sanitized.truncate(sanitized.trim_end().len());
+        sanitized.push_str(suffix);
+        sanitized
+    }
+
+    /// Names to subscribe with, starting from `name` as given
+    pub fn candidates(&self, name: &str) -> NameCandidates {
+        NameCandidates { policy: self.clone(), base: name.to_string(), suffix: None, sanitized: false, tried: vec![name.to_string()] }
+    }
+}
+
+/// Names tried for one subscription, following a `NamePolicy`
+#[derive(Debug, Clone)]
+pub struct NameCandidates {
+    policy: NamePolicy,
+    base: String,
+    suffix: Option<u32>,
+    sanitized: bool,
+    tried: Vec<String>,
+}
+
+impl NameCandidates {
+    /// Name to try once the last one was refused with `err`, `None` when there is none left
+    pub fn next(&mut self, err: &SubscribeError) -> Option<String> {
+        if self.tried.len() >= self.policy.max_attempts {
+            return None;
+        }
+        match err {
+            SubscribeError::AlreadyRegistered => self.suffix = Some(self.suffix.map_or(self.policy.first_suffix, |suffix| suffix + 1)),
+            // A sanitized name that is still invalid will not get any better
+            SubscribeError::InvalidName if self.sanitized => return None,
+            SubscribeError::InvalidName => self.sanitized = true,
+        }
+        let name = self.current();
+        if name.trim().is_empty() || self.tried.contains(&name) {
+            return None;
+        }
+        self.tried.push(name.clone());
+        Some(name)
+    }
+
+    fn current(&self) -> String {
+        let suffix = self.suffix.map_or_else(String::new, |suffix| format!("{}{suffix}", self.policy.separator));
+        if self.sanitized {
+            self.policy.sanitize(&self.base, &suffix)
+        } else {
+            format!("{}{suffix}", self.base)
+        }
+    }
+
+    /// Every name tried so far, oldest first
+    pub fn tried(&self) -> &[String] {
+        &self.tried
+    }
+}
+
+#[cfg(test)]
+mod tests_name_policy {
+    use super::*;
+    use crate::game_server::validate_name;
+
+    #[test]
+    fn is_name_policy_suffixed() {
+        let mut candidates = NamePolicy::default().candidates("bob");
+        assert_eq!(candidates.next(&SubscribeError::AlreadyRegistered), Some("bob-2".to_string()));
+        assert_eq!(candidates.next(&SubscribeError::AlreadyRegistered), Some("bob-3".to_string()));
+        assert_eq!(candidates.tried(), ["bob", "bob-2", "bob-3"]);
+
+        let mut candidates = NamePolicy::default().with_separator("_").with_max_attempts(2).candidates("bob");
+        assert_eq!(candidates.next(&SubscribeError::AlreadyRegistered), Some("bob_2".to_string()));
+        assert_eq!(candidates.next(&SubscribeError::AlreadyRegistered), None);
+    }
+
+    #[test]
+    fn is_name_policy_sanitized() {
+        let policy = NamePolicy::default();
+        assert_eq!(policy.sanitize(" free \"patato\"\n", ""), "free__patato_");
+        assert_eq!(policy.with_replacement(None).with_max_length(6).sanitize("héllo wörld", "-2"), "hllo-2");
+        assert_eq!(NamePolicy::default().with_charset(Charset::Printable).sanitize("a\u{7}é b ", ""), "a_é b");
+
+        let mut candidates = NamePolicy::default().candidates("a\tb");
+        assert_eq!(candidates.next(&SubscribeError::InvalidName), Some("a_b".to_string()));
+        assert_eq!(candidates.next(&SubscribeError::AlreadyRegistered), Some("a_b-2".to_string()));
+        assert_eq!(candidates.next(&SubscribeError::InvalidName), None);
+    }
+
+    #[test]
+    fn is_name_policy_giving_up() {
+        let mut candidates = NamePolicy::default().candidates("\u{1}\u{2}");
+        assert_eq!(NamePolicy::default().with_replacement(None).candidates("\u{1}").next(&SubscribeError::InvalidName), None);
+        assert_eq!(candidates.next(&SubscribeError::InvalidName), Some("__".to_string()));
+
+        // Same name once sanitized: asking again cannot succeed
+        assert_eq!(NamePolicy::default().candidates("bob").next(&SubscribeError::InvalidName), None);
+    }
+
+    #[test]
+    fn is_name_policy_accepted_by_server() {
+        let long = "x".repeat(40);
+        let taken = [long.chars().take(32).collect::<String>()];
+        let mut candidates = NamePolicy::default().candidates(&long);
+        let mut name = long.clone();
+        let mut err = validate_name(&name, taken.iter().map(String::as_str));
+        while let Err(refused) = err {
+            name = candidates.next(&refused).expect("a name is found");
+            err = validate_name(&name, taken.iter().map(String::as_str));
+        }
+        assert_eq!(name, format!("{}-2", "x".repeat(30)));
+    }
+}
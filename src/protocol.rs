@@ -0,0 +1,223 @@
+//! Protocol messages, one module per version. The client and the server use the latest one,
+//! re-exported here; an older or newer version gets its own module next to `v1` and `v2`, and
+//! its entry in `SUPPORTED_VERSIONS` and `challenge_kinds`.
+
+use std::io::{Read, Write};
+use std::str;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::frame_codec::{FrameCodec, FrameError};
+
+pub mod v1;
+pub mod v2;
+
+pub use v2::*;
+
+/// Reads one frame and decodes the message it holds
+pub fn read_message<R: Read>(reader: &mut R, codec: &FrameCodec) -> Result<Message, FrameError> {
+    decode_message(&codec.read_frame(reader)?)
+}
+
+pub fn write_message<W: Write>(writer: &mut W, codec: &FrameCodec, message: &Message) -> Result<(), FrameError> {
+    codec.write_frame(writer, &encode_message(message)?)
+}
+
+/// Decodes a message, the variants unknown to this protocol version included (`Message::Unknown`)
+pub fn decode_message(frame: &[u8]) -> Result<Message, FrameError> {
+    Message::from_value(decode_frame(frame)?).map_err(FrameError::Decode)
+}
+
+/// Decodes a message of protocol `version` into the latest one's, the variants `version` does
+/// not have included (`Message::Unknown`); a version we do not speak is decoded as the latest
+pub fn decode_message_of(version: i32, frame: &[u8]) -> Result<Message, FrameError> {
+    match version {
+        v1::VERSION => v1::Message::from_value(decode_frame(frame)?).map(Message::from).map_err(FrameError::Decode),
+        _ => decode_message(frame),
+    }
+}
+
+pub fn encode_message(message: &Message) -> Result<Vec<u8>, FrameError> {
+    encode_frame(message)
+}
+
+/// Decodes a frame holding a message of any version
+pub fn decode_frame<M: DeserializeOwned>(frame: &[u8]) -> Result<M, FrameError> {
+    let message = str::from_utf8(frame).map_err(FrameError::InvalidUtf8)?;
+    serde_json::from_str(message).map_err(FrameError::Decode)
+}
+
+pub fn encode_frame<M: Serialize>(message: &M) -> Result<Vec<u8>, FrameError> {
+    serde_json::to_vec(message).map_err(FrameError::Encode)
+}
+
+/// Protocol versions this crate speaks, oldest first
+pub const SUPPORTED_VERSIONS: [i32; 2] = [v1::VERSION, v2::VERSION];
+
+/// `Challenge` variants of protocol `version`, `None` for a version we do not speak
+pub fn challenge_kinds(version: i32) -> Option<&'static [&'static str]> {
+    match version {
+        v1::VERSION => Some(&v1::CHALLENGE_KINDS),
+        v2::VERSION => Some(&v2::CHALLENGE_KINDS),
+        _ => None,
+    }
+}
+
+/// How we talk to a server, from the version of its `Welcome`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Negotiation {
+    /// We speak the version of the server
+    Supported(i32),
+    /// The server is newer: we speak `version`, our latest one, and only play its challenges
+    Degraded { server: i32, version: i32 },
+    /// The server is older than any version we speak, or newer and newer servers are refused
+    Unsupported(i32),
+}
+
+impl Negotiation {
+    /// Version to speak, `None` when the game cannot be played
+    pub fn version(self) -> Option<i32> {
+        match self {
+            Negotiation::Supported(version) | Negotiation::Degraded { version, .. } => Some(version),
+            Negotiation::Unsupported(_) => None,
+        }
+    }
+}
+
+/// Names of the policies, as given to `UnknownPolicy::named`
+pub const UNKNOWN_POLICY_NAMES: [&str; 3] = ["ignore", "fallback", "abort"];
+
+/// What to do with a message or a challenge this client does not know
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownPolicy {
+    /// Log it and go on, leaving a challenge unanswered
+    Ignore,
+    /// Log it, and answer a challenge with `ChallengeAnswer::fallback`
+    Fallback,
+    /// Stop playing
+    Abort,
+}
+
+impl UnknownPolicy {
+    pub fn named(name: &str) -> Option<UnknownPolicy> {
+        match name {
+            "ignore" => Some(UnknownPolicy::Ignore),
+            "fallback" => Some(UnknownPolicy::Fallback),
+            "abort" => Some(UnknownPolicy::Abort),
+            _ => None,
+        }
+    }
+}
+
+/// Compares the version announced by the server with `SUPPORTED_VERSIONS`
+pub fn negotiate(server: i32, accept_newer: bool) -> Negotiation {
+    let latest = SUPPORTED_VERSIONS[SUPPORTED_VERSIONS.len() - 1];
+    if SUPPORTED_VERSIONS.contains(&server) {
+        Negotiation::Supported(server)
+    } else if server > latest && accept_newer {
+        Negotiation::Degraded { server, version: latest }
+    } else {
+        Negotiation::Unsupported(server)
+    }
+}
+
+#[cfg(test)]
+mod tests_message {
+    use super::*;
+    use crate::monstrous_maze_challenge::MonstrousMazeInput;
+
+    const GRID: &str = "┌─┬───┐\n│Y│ M │\n│ └─┐ │\n│    X│\n└─────┘";
+
+    #[test]
+    fn is_message_decoding_multiline_grid() {
+        let frame = serde_json::json!({"Challenge": {"MonstrousMaze": {"grid": GRID, "endurance": 2}}}).to_string();
+        match decode_message(frame.as_bytes()) {
+            Ok(Message::Challenge(Challenge::MonstrousMaze(input))) => {
+                assert_eq!(input.grid, GRID);
+                assert_eq!(input.grid.lines().count(), 5);
+                assert_eq!(input.endurance, 2);
+            }
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[test]
+    fn is_message_round_trip_grid() {
+        let message = Message::Challenge(Challenge::MonstrousMaze(MonstrousMazeInput { grid: GRID.to_string(), endurance: 1 }));
+        let frame = encode_message(&message).unwrap();
+        match decode_message(&frame) {
+            Ok(Message::Challenge(Challenge::MonstrousMaze(input))) => assert_eq!(input.grid, GRID),
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[test]
+    fn is_message_round_trip_quoted_name() {
+        let message = Message::Subscribe(Subscribe { name: "free \"patato\"\\".to_string() });
+        let frame = encode_message(&message).unwrap();
+        match decode_message(&frame) {
+            Ok(Message::Subscribe(subscribe)) => assert_eq!(subscribe.name, "free \"patato\"\\"),
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[test]
+    fn is_message_decoding_invalid_utf8() {
+        assert!(matches!(decode_message(&[b'"', 0xff, b'"']), Err(FrameError::InvalidUtf8(_))));
+        assert!(matches!(decode_message(b"{\"Nope\""), Err(FrameError::Decode(_))));
+    }
+
+    #[test]
+    fn is_message_decoding_unknown() {
+        let frame = serde_json::json!({"Shout": {"volume": 11}}).to_string();
+        assert!(matches!(decode_message(frame.as_bytes()), Ok(Message::Unknown(raw)) if raw["Shout"]["volume"] == 11));
+        assert!(matches!(decode_message(b"\"Goodbye\""), Ok(Message::Unknown(raw)) if raw == "Goodbye"));
+
+        let frame = serde_json::json!({"Challenge": {"Sudoku": {"grid": "1.3"}}}).to_string();
+        match decode_message(frame.as_bytes()) {
+            Ok(Message::Challenge(Challenge::Unknown(raw))) => assert_eq!(variant_tag(&raw), Some("Sudoku")),
+            other => panic!("unexpected message {other:?}"),
+        }
+        assert!(encode_message(&Message::Unknown(serde_json::json!("Goodbye"))).is_err());
+    }
+
+    #[test]
+    fn is_message_decoding_per_version() {
+        let frame = serde_json::json!({"Challenge": {"SHA1HashCash": {"complexity": 4, "message": "hi"}}}).to_string();
+        assert!(matches!(decode_message_of(v2::VERSION, frame.as_bytes()), Ok(Message::Challenge(Challenge::SHA1HashCash(_)))));
+        match decode_message_of(v1::VERSION, frame.as_bytes()) {
+            Ok(Message::Challenge(Challenge::Unknown(raw))) => assert_eq!(variant_tag(&raw), Some("SHA1HashCash")),
+            other => panic!("unexpected message {other:?}"),
+        }
+        let frame = serde_json::json!({"Challenge": {"MD5HashCash": {"complexity": 4, "message": "hi"}}}).to_string();
+        assert!(matches!(decode_message_of(v1::VERSION, frame.as_bytes()), Ok(Message::Challenge(Challenge::MD5HashCash(_)))));
+        assert!(matches!(v1::Message::from_value(serde_json::json!({"Challenge": {"SHA256HashCash": null}})), Ok(v1::Message::Challenge(v1::Challenge::Unknown(_)))));
+    }
+
+    #[test]
+    fn is_message_decoding_known_invalid() {
+        // Known variants with the wrong content are errors, not unknown messages
+        for frame in [r#"{"Welcome":{"version":"one"}}"#, r#"{"Challenge":{"MonstrousMaze":{"grid":3}}}"#, "[]"] {
+            assert!(matches!(decode_message(frame.as_bytes()), Err(FrameError::Decode(_))), "{frame}");
+        }
+        for kind in MESSAGE_KINDS {
+            let frame = serde_json::json!({ kind: null }).to_string();
+            assert!(!matches!(decode_message(frame.as_bytes()), Ok(Message::Unknown(_))), "{kind}");
+        }
+        let frame = encode_message(&Message::ChallengeResult(ChallengeResult { answer: ChallengeAnswer::fallback(), next_target: String::new() })).unwrap();
+        assert!(matches!(decode_message(&frame), Ok(Message::ChallengeResult(_))));
+    }
+
+    #[test]
+    fn is_message_version_negotiated() {
+        assert_eq!(negotiate(v1::VERSION, false), Negotiation::Supported(1));
+        assert_eq!(negotiate(v2::VERSION, false), Negotiation::Supported(2));
+        assert_eq!(negotiate(3, true), Negotiation::Degraded { server: 3, version: 2 });
+        assert_eq!(negotiate(3, false).version(), None);
+        assert_eq!(negotiate(0, true), Negotiation::Unsupported(0));
+        for version in SUPPORTED_VERSIONS {
+            assert!(challenge_kinds(version).is_some());
+        }
+        assert_eq!(challenge_kinds(0), None);
+    }
+}
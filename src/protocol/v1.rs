@@ -0,0 +1,152 @@
+//! Messages of protocol version 1
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::hash_cash_challenge::{MD5HashCashInput, MD5HashCashOutput};
+use crate::monstrous_maze_challenge::{MonstrousMazeInput, MonstrousMazeOutput};
+use crate::recover_secret_challenge::{RecoverSecretInput, RecoverSecretOutput};
+
+/// Version announced by a `Welcome` of this protocol
+pub const VERSION: i32 = 1;
+
+/// Every `Message` variant of this protocol, `Unknown` aside
+pub const MESSAGE_KINDS: [&str; 9] = [
+    "Hello", "Welcome", "Subscribe", "SubscribeResult", "PublicLeaderBoard", "Challenge", "ChallengeResult", "RoundSummary", "EndOfGame",
+];
+
+/// Every `Challenge` variant of this protocol, `Unknown` aside
+pub const CHALLENGE_KINDS: [&str; 3] = ["MD5HashCash", "MonstrousMaze", "RecoverSecret"];
+
+/// Variant name of an externally tagged enum serialized as `value`
+pub fn variant_tag(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(tag) => Some(tag),
+        Value::Object(fields) if fields.len() == 1 => fields.keys().next().map(String::as_str),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Welcome {
+    pub version: i32
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscribe {
+    pub name: String
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SubscribeError {
+    AlreadyRegistered,
+    InvalidName
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SubscribeResult {
+    Ok,
+    Err(SubscribeError)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Message {
+    Hello,
+    Welcome(Welcome),
+    Subscribe(Subscribe),
+    SubscribeResult(SubscribeResult),
+    PublicLeaderBoard(PublicLeaderBoard),
+    Challenge(Challenge),
+    ChallengeResult(ChallengeResult),
+    RoundSummary(RoundSummary),
+    EndOfGame(EndOfGame),
+    /// A message of a newer protocol, as received; only `Message::from_value` builds it and it
+    /// cannot be sent
+    #[serde(skip)]
+    Unknown(Value),
+}
+
+impl Message {
+    /// Decodes `value`, keeping the variants this protocol does not have, including challenges,
+    /// as `Unknown`. A known variant with unexpected content is still an error.
+    pub fn from_value(value: Value) -> serde_json::Result<Message> {
+        match variant_tag(&value) {
+            Some(tag) if !MESSAGE_KINDS.contains(&tag) => Ok(Message::Unknown(value)),
+            Some("Challenge") => match &value["Challenge"] {
+                challenge if variant_tag(challenge).is_some_and(|tag| !CHALLENGE_KINDS.contains(&tag)) => {
+                    Ok(Message::Challenge(Challenge::Unknown(challenge.clone())))
+                }
+                _ => serde_json::from_value(value),
+            },
+            _ => serde_json::from_value(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublicLeaderBoard(pub Vec<PublicPlayer>);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicPlayer {
+    pub name: String,
+    pub stream_id: String,
+    pub score: i32,
+    pub steps: u32,
+    pub is_active: bool,
+    pub total_used_time: f64
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Challenge {
+    MD5HashCash(MD5HashCashInput),
+    MonstrousMaze(MonstrousMazeInput),
+    RecoverSecret(RecoverSecretInput),
+    /// A challenge of a newer protocol, as received
+    #[serde(skip)]
+    Unknown(Value),
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChallengeAnswer {
+   MD5HashCash(MD5HashCashOutput),
+   MonstrousMaze(MonstrousMazeOutput),
+   RecoverSecret(RecoverSecretOutput)
+}
+
+impl ChallengeAnswer {
+    /// Answer to a challenge that cannot be solved: any server of this version decodes it, and
+    /// counts it as a wrong answer
+    pub fn fallback() -> Self {
+        ChallengeAnswer::MD5HashCash(MD5HashCashOutput::default())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeResult {
+    pub answer: ChallengeAnswer,
+    pub next_target: String
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChallengeValue {
+    Unreachable,
+    Timeout,
+    BadResult { used_time: f64, next_target: String },
+    Ok { used_time: f64, next_target: String }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportedChallengeResult {
+    pub name: String, //"free_patato"
+    pub value: ChallengeValue
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundSummary {
+    pub challenge: String,
+    pub chain: Vec<ReportedChallengeResult>
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndOfGame{
+    pub leader_board: PublicLeaderBoard
+}
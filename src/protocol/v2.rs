@@ -0,0 +1,127 @@
+//! Messages of protocol version 2: those of version 1, with the SHA-1 and SHA-256 hash cash
+//! challenges
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use super::v1;
+use crate::hash_cash_challenge::{MD5HashCashInput, MD5HashCashOutput, SHA1HashCashInput, SHA1HashCashOutput, SHA256HashCashInput, SHA256HashCashOutput};
+use crate::monstrous_maze_challenge::{MonstrousMazeInput, MonstrousMazeOutput};
+use crate::recover_secret_challenge::{RecoverSecretInput, RecoverSecretOutput};
+
+pub use super::v1::{
+    variant_tag, ChallengeValue, EndOfGame, PublicLeaderBoard, PublicPlayer, ReportedChallengeResult, RoundSummary, Subscribe, SubscribeError, SubscribeResult, Welcome, MESSAGE_KINDS,
+};
+
+/// Version announced by a `Welcome` of this protocol
+pub const VERSION: i32 = 2;
+
+/// Every `Challenge` variant of this protocol, `Unknown` aside
+pub const CHALLENGE_KINDS: [&str; 5] = ["MD5HashCash", "MonstrousMaze", "RecoverSecret", "SHA1HashCash", "SHA256HashCash"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Message {
+    Hello,
+    Welcome(Welcome),
+    Subscribe(Subscribe),
+    SubscribeResult(SubscribeResult),
+    PublicLeaderBoard(PublicLeaderBoard),
+    Challenge(Challenge),
+    ChallengeResult(ChallengeResult),
+    RoundSummary(RoundSummary),
+    EndOfGame(EndOfGame),
+    /// A message of a newer protocol, as received; only `Message::from_value` builds it and it
+    /// cannot be sent
+    #[serde(skip)]
+    Unknown(Value),
+}
+
+impl Message {
+    /// Decodes `value`, keeping the variants this protocol does not have, including challenges,
+    /// as `Unknown`. A known variant with unexpected content is still an error.
+    pub fn from_value(value: Value) -> serde_json::Result<Message> {
+        match variant_tag(&value) {
+            Some(tag) if !MESSAGE_KINDS.contains(&tag) => Ok(Message::Unknown(value)),
+            Some("Challenge") => match &value["Challenge"] {
+                challenge if variant_tag(challenge).is_some_and(|tag| !CHALLENGE_KINDS.contains(&tag)) => {
+                    Ok(Message::Challenge(Challenge::Unknown(challenge.clone())))
+                }
+                _ => serde_json::from_value(value),
+            },
+            _ => serde_json::from_value(value),
+        }
+    }
+}
+
+impl From<v1::Message> for Message {
+    fn from(message: v1::Message) -> Self {
+        match message {
+            v1::Message::Hello => Message::Hello,
+            v1::Message::Welcome(welcome) => Message::Welcome(welcome),
+            v1::Message::Subscribe(subscribe) => Message::Subscribe(subscribe),
+            v1::Message::SubscribeResult(result) => Message::SubscribeResult(result),
+            v1::Message::PublicLeaderBoard(board) => Message::PublicLeaderBoard(board),
+            v1::Message::Challenge(challenge) => Message::Challenge(challenge.into()),
+            v1::Message::ChallengeResult(result) => Message::ChallengeResult(ChallengeResult { answer: result.answer.into(), next_target: result.next_target }),
+            v1::Message::RoundSummary(summary) => Message::RoundSummary(summary),
+            v1::Message::EndOfGame(end) => Message::EndOfGame(end),
+            v1::Message::Unknown(value) => Message::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Challenge {
+    MD5HashCash(MD5HashCashInput),
+    MonstrousMaze(MonstrousMazeInput),
+    RecoverSecret(RecoverSecretInput),
+    SHA1HashCash(SHA1HashCashInput),
+    SHA256HashCash(SHA256HashCashInput),
+    /// A challenge of a newer protocol, as received
+    #[serde(skip)]
+    Unknown(Value),
+}
+
+impl From<v1::Challenge> for Challenge {
+    fn from(challenge: v1::Challenge) -> Self {
+        match challenge {
+            v1::Challenge::MD5HashCash(input) => Challenge::MD5HashCash(input),
+            v1::Challenge::MonstrousMaze(input) => Challenge::MonstrousMaze(input),
+            v1::Challenge::RecoverSecret(input) => Challenge::RecoverSecret(input),
+            v1::Challenge::Unknown(value) => Challenge::Unknown(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChallengeAnswer {
+   MD5HashCash(MD5HashCashOutput),
+   MonstrousMaze(MonstrousMazeOutput),
+   RecoverSecret(RecoverSecretOutput),
+   SHA1HashCash(SHA1HashCashOutput),
+   SHA256HashCash(SHA256HashCashOutput),
+}
+
+impl ChallengeAnswer {
+    /// Answer to a challenge that cannot be solved: any server of this version or of version 1
+    /// decodes it, and counts it as a wrong answer
+    pub fn fallback() -> Self {
+        v1::ChallengeAnswer::fallback().into()
+    }
+}
+
+impl From<v1::ChallengeAnswer> for ChallengeAnswer {
+    fn from(answer: v1::ChallengeAnswer) -> Self {
+        match answer {
+            v1::ChallengeAnswer::MD5HashCash(output) => ChallengeAnswer::MD5HashCash(output),
+            v1::ChallengeAnswer::MonstrousMaze(output) => ChallengeAnswer::MonstrousMaze(output),
+            v1::ChallengeAnswer::RecoverSecret(output) => ChallengeAnswer::RecoverSecret(output),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeResult {
+    pub answer: ChallengeAnswer,
+    pub next_target: String
+}
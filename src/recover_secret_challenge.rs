@@ -0,0 +1,442 @@
+use crate::challenge_trait::ChallengeTrait;
+use dictionary::Dictionary;
+pub mod dictionary;
+pub mod generator;
+
+use crate::solve_control::{Progress, SolveLimits, Solved, StopReason};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverSecretInput {
+    pub word_count: usize,
+    pub letters: String,
+    pub tuple_sizes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecoverSecretOutput {
+    pub secret_sentence: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RecoverSecretError {
+    /// `tuple_sizes` does not add up to the number of letters
+    TupleSizesMismatch { letters: usize, tuple_sizes: usize },
+}
+
+impl fmt::Display for RecoverSecretError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecoverSecretError::TupleSizesMismatch { letters, tuple_sizes } => write!(f, "tuple sizes add up to {tuple_sizes} but there are {letters} letters"),
+        }
+    }
+}
+
+pub struct RecoverSecret {
+    pub input: RecoverSecretInput,
+    pub tuples: Vec<Vec<char>>,
+    pub dictionary: Option<Arc<Dictionary>>,
+}
+
+impl RecoverSecret {
+    /// States kept per sentence length when the search space gets too wide
+    const BEAM_WIDTH: usize = 2_000;
+    /// Number of shortest sentences kept when several of them match the tuples
+    const MAX_CANDIDATES: usize = 64;
+    /// Number of shortest sentences compared against the dictionary
+    const MAX_DICTIONARY_CANDIDATES: usize = 1_024;
+    /// Sentences kept for each search state, so that wide layers stay cheap whatever the limit
+    const MAX_SENTENCES_PER_STATE: usize = 16;
+
+    /// Ranks the candidate sentences by how many of their words are in `dictionary`
+    pub fn with_dictionary(mut self, dictionary: Arc<Dictionary>) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    fn split_tuples(input: &RecoverSecretInput) -> Result<Vec<Vec<char>>, RecoverSecretError> {
+        let letters: Vec<char> = input.letters.chars().collect();
+        let tuple_sizes: usize = input.tuple_sizes.iter().sum();
+        if tuple_sizes != letters.len() {
+            return Err(RecoverSecretError::TupleSizesMismatch { letters: letters.len(), tuple_sizes });
+        }
+
+        let mut tuples = vec![];
+        let mut offset = 0;
+        for size in &input.tuple_sizes {
+            tuples.push(letters[offset..offset + size].to_vec());
+            offset += size;
+        }
+        Ok(tuples)
+    }
+
+    fn is_subsequence(tuple: &[char], sentence: &[char]) -> bool {
+        let mut sentence = sentence.iter();
+        tuple.iter().all(|letter| sentence.any(|c| c == letter))
+    }
+
+    fn required_spaces(&self) -> usize {
+        self.input.word_count.saturating_sub(1)
+    }
+
+    fn is_goal(&self, state: &SearchState) -> bool {
+        state.positions.iter().zip(&self.tuples).all(|(&position, tuple)| position == tuple.len())
+            && state.spaces == self.required_spaces()
+            && !state.last_was_space
+    }
+
+    /// Characters to emit from `state`: the next character of any tuple, plus a space while
+    /// the sentence still needs more words.
+    ///
+    /// A character that no tuple still waits for further down can be emitted right away
+    /// without making the sentence longer, so when such characters exist only they are tried.
+    fn moves(&self, state: &SearchState, is_empty: bool) -> BTreeSet<char> {
+        let remainders: Vec<&[char]> = state.positions.iter().zip(&self.tuples)
+            .map(|(&position, tuple)| &tuple[position..])
+            .collect();
+        let mut moves: BTreeSet<char> = remainders.iter()
+            .filter_map(|remainder| remainder.first().copied())
+            .filter(|&c| c != SPACE)
+            .collect();
+        if !is_empty && !state.last_was_space && state.spaces < self.required_spaces() {
+            moves.insert(SPACE);
+        }
+
+        let is_unblocked = |c: &char| remainders.iter().all(|remainder| match remainder.split_first() {
+            Some((first, rest)) => first == c || !rest.contains(c),
+            None => true,
+        });
+        let unblocked: BTreeSet<char> = moves.iter().copied().filter(is_unblocked).collect();
+        if unblocked.is_empty() { moves } else { unblocked }
+    }
+
+    fn advance(&self, state: &SearchState, c: char) -> SearchState {
+        let positions = state.positions.iter().zip(&self.tuples)
+            .map(|(&position, tuple)| if tuple.get(position) == Some(&c) { position + 1 } else { position })
+            .collect();
+        let spaces = state.spaces + usize::from(c == SPACE);
+        SearchState { positions, spaces, last_was_space: c == SPACE }
+    }
+
+    /// Lower bound on the characters still to emit: each character is needed at least as
+    /// many times as it remains in the most demanding tuple. `None` when the state can no
+    /// longer reach the required number of spaces.
+    fn remaining_estimate(&self, suffix_counts: &SuffixCounts, state: &SearchState) -> Option<usize> {
+        let mut needed = vec![0; suffix_counts.alphabet.len()];
+        for (tuple, &position) in suffix_counts.counts.iter().zip(&state.positions) {
+            for (needed, &count) in needed.iter_mut().zip(&tuple[position]) {
+                *needed = (*needed).max(count as usize);
+            }
+        }
+
+        let missing_spaces = self.required_spaces().checked_sub(state.spaces)?;
+        if let Some(space) = suffix_counts.space {
+            if needed[space] > missing_spaces {
+                return None;
+            }
+            needed[space] = 0;
+        }
+        Some(needed.iter().sum::<usize>() + missing_spaces)
+    }
+
+    fn remaining_letters(&self, state: &SearchState) -> usize {
+        state.positions.iter().zip(&self.tuples).map(|(&position, tuple)| tuple.len() - position).sum()
+    }
+
+    /// Shortest sentences holding every tuple as a subsequence and `word_count` words, up to `limit`.
+    ///
+    /// Breadth-first over the progress made in each tuple, one emitted character per layer.
+    /// Layers wider than `BEAM_WIDTH` only keep the states closest to completion, and each
+    /// state keeps at most `MAX_SENTENCES_PER_STATE` of the sentences reaching it, so the
+    /// result is exact for small inputs and the shortest sentences found otherwise.
+    /// `limits` are checked between layers.
+    pub fn shortest_candidates(&self, limit: usize, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Result<Vec<String>, StopReason> {
+        let suffix_counts = SuffixCounts::new(&self.tuples);
+        let start = SearchState { positions: vec![0; self.tuples.len()], spaces: 0, last_was_space: false };
+        if self.remaining_estimate(&suffix_counts, &start).is_none() {
+            return Ok(vec![]);
+        }
+
+        let max_length = self.tuples.iter().map(Vec::len).sum::<usize>() + self.required_spaces();
+        let mut beam: Vec<(SearchState, Vec<String>)> = vec![(start, vec![String::new()])];
+        let mut explored: u64 = 0;
+        let per_state = limit.min(RecoverSecret::MAX_SENTENCES_PER_STATE);
+        for length in 0..=max_length {
+            let goals: Vec<String> = beam.iter()
+                .filter(|(state, _)| self.is_goal(state))
+                .flat_map(|(_, sentences)| sentences.iter().cloned())
+                .take(limit)
+                .collect();
+            if !goals.is_empty() {
+                return Ok(goals);
+            }
+            if let Some(reason) = limits.stop_reason() {
+                return Err(reason);
+            }
+
+            let mut next_layer: HashMap<SearchState, Vec<(usize, char)>> = HashMap::new();
+            for (index, (state, _)) in beam.iter().enumerate() {
+                for c in self.moves(state, length == 0) {
+                    next_layer.entry(self.advance(state, c)).or_default().push((index, c));
+                }
+            }
+
+            explored += next_layer.len() as u64;
+            progress(Progress::ExploredStates(explored));
+
+            let mut scored: Vec<_> = next_layer.into_iter()
+                .filter_map(|(state, parents)| {
+                    let estimate = self.remaining_estimate(&suffix_counts, &state)?;
+                    Some(((estimate, self.remaining_letters(&state)), state, parents))
+                })
+                .collect();
+            scored.sort_unstable_by(|(score, _, parents), (other_score, _, other_parents)| score.cmp(other_score).then_with(|| parents.cmp(other_parents)));
+            scored.truncate(RecoverSecret::BEAM_WIDTH);
+
+            beam = scored.into_iter().map(|(_, state, parents)| {
+                let sentences = parents.iter()
+                    .flat_map(|&(index, c)| beam[index].1.iter().map(move |sentence| format!("{sentence}{c}")))
+                    .take(per_state)
+                    .collect();
+                (state, sentences)
+            }).collect();
+        }
+        Ok(vec![])
+    }
+
+    /// Candidate sentences, best first: the shortest ones, those with the most dictionary
+    /// words ahead when a dictionary is set, then the greedy sentence as a last resort.
+    pub fn ranked_candidates(&self) -> Vec<String> {
+        self.ranked_candidates_within(&SolveLimits::default(), &mut |_| {}).unwrap_or_default()
+    }
+
+    fn ranked_candidates_within(&self, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Result<Vec<String>, StopReason> {
+        let mut candidates = match &self.dictionary {
+            Some(dictionary) => {
+                let mut candidates = self.shortest_candidates(RecoverSecret::MAX_DICTIONARY_CANDIDATES, limits, progress)?;
+                candidates.sort_by_cached_key(|candidate| Reverse(dictionary.score(candidate)));
+                candidates
+            }
+            None => self.shortest_candidates(RecoverSecret::MAX_CANDIDATES, limits, progress)?,
+        };
+        candidates.push(self.greedy_candidate());
+        Ok(candidates)
+    }
+
+    /// Sentence for inputs where the search finds nothing: repeatedly emits the
+    /// character that advances the most tuples, then splits the longest words until there
+    /// are `word_count` of them.
+    fn greedy_candidate(&self) -> String {
+        let mut state = SearchState { positions: vec![0; self.tuples.len()], spaces: 0, last_was_space: false };
+        let mut sentence: Vec<char> = vec![];
+        loop {
+            let mut votes: BTreeMap<char, usize> = BTreeMap::new();
+            for (&position, tuple) in state.positions.iter().zip(&self.tuples) {
+                if let Some(&c) = tuple.get(position) {
+                    if c != SPACE || !(sentence.is_empty() || state.last_was_space) {
+                        *votes.entry(c).or_insert(0) += 1;
+                    }
+                }
+            }
+            let Some((&c, _)) = votes.iter().max_by_key(|(_, &count)| count) else { break };
+            state = self.advance(&state, c);
+            sentence.push(c);
+        }
+
+        let mut words: Vec<String> = sentence.iter().collect::<String>().split_whitespace().map(str::to_string).collect();
+        while words.len() < self.input.word_count {
+            let Some((index, longest)) = words.iter().enumerate().filter(|(_, word)| word.chars().count() > 1).max_by_key(|(_, word)| word.chars().count()) else { break };
+            let middle = longest.char_indices().nth(longest.chars().count() / 2).map_or(0, |(i, _)| i);
+            let (left, right) = longest.split_at(middle);
+            let (left, right) = (left.to_string(), right.to_string());
+            words.splice(index..=index, [left, right]);
+        }
+        words.join(" ")
+    }
+}
+
+const SPACE: char = ' ';
+
+/// For every tuple and position, how many times each character of the alphabet is left
+struct SuffixCounts {
+    alphabet: Vec<char>,
+    space: Option<usize>,
+    counts: Vec<Vec<Vec<u8>>>,
+}
+
+impl SuffixCounts {
+    fn new(tuples: &[Vec<char>]) -> Self {
+        let alphabet: Vec<char> = tuples.iter().flatten().copied().collect::<BTreeSet<char>>().into_iter().collect();
+        let space = alphabet.iter().position(|&c| c == SPACE);
+        let counts = tuples.iter().map(|tuple| {
+            let mut suffixes = vec![vec![0u8; alphabet.len()]; tuple.len() + 1];
+            for position in (0..tuple.len()).rev() {
+                suffixes[position] = suffixes[position + 1].clone();
+                let letter = alphabet.binary_search(&tuple[position]).unwrap_or_default();
+                suffixes[position][letter] = suffixes[position][letter].saturating_add(1);
+            }
+            suffixes
+        }).collect();
+        SuffixCounts { alphabet, space, counts }
+    }
+}
+
+/// Progress in every tuple, plus what is needed to place the spaces between words
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct SearchState {
+    positions: Vec<usize>,
+    spaces: usize,
+    last_was_space: bool,
+}
+
+impl ChallengeTrait for RecoverSecret {
+    type Input = RecoverSecretInput;
+    type Output = RecoverSecretOutput;
+    type Error = RecoverSecretError;
+
+    fn name() -> String {
+        "recoverSecret".to_string()
+    }
+
+    fn new(input: Self::Input) -> Result<Self, Self::Error> {
+        let tuples = RecoverSecret::split_tuples(&input)?;
+        Ok(RecoverSecret { input, tuples, dictionary: None })
+    }
+
+    fn solve(&self) -> Self::Output {
+        let secret_sentence = self.ranked_candidates().into_iter().next().unwrap_or_default();
+        RecoverSecretOutput { secret_sentence }
+    }
+
+    /// Falls back to the greedy sentence, valid but often longer, when stopped early
+    fn solve_within(&self, limits: &SolveLimits, progress: &mut dyn FnMut(Progress)) -> Solved<Self::Output> {
+        match self.ranked_candidates_within(limits, progress) {
+            Ok(candidates) => Solved::Answer(RecoverSecretOutput { secret_sentence: candidates.into_iter().next().unwrap_or_default() }),
+            Err(reason) => Solved::BestSoFar { answer: RecoverSecretOutput { secret_sentence: self.greedy_candidate() }, reason },
+        }
+    }
+
+    fn verify(&self, answer: &Self::Output) -> bool {
+        let sentence: Vec<char> = answer.secret_sentence.chars().collect();
+        answer.secret_sentence.split_whitespace().count() == self.input.word_count
+            && self.tuples.iter().all(|tuple| RecoverSecret::is_subsequence(tuple, &sentence))
+    }
+}
+
+#[cfg(test)]
+mod tests_recover_secret {
+    use super::*;
+    use std::time::Duration;
+
+    fn recover_secret(word_count: usize, letters: &str, tuple_sizes: Vec<usize>) -> RecoverSecret {
+        RecoverSecret::new(RecoverSecretInput { word_count, letters: letters.to_string(), tuple_sizes }).unwrap()
+    }
+
+    #[test]
+    fn is_recover_secret_name() {
+        assert_eq!(RecoverSecret::name(), String::from("recoverSecret"));
+    }
+
+    #[test]
+    fn is_recover_secret_new() {
+        let new_secret = recover_secret(1, "abcde", vec![3, 2]);
+        assert_eq!(new_secret.tuples, vec![vec!['a', 'b', 'c'], vec!['d', 'e']]);
+
+        let mismatch = RecoverSecret::new(RecoverSecretInput { word_count: 1, letters: "abc".to_string(), tuple_sizes: vec![2, 2] });
+        assert_eq!(mismatch.err(), Some(RecoverSecretError::TupleSizesMismatch { letters: 3, tuple_sizes: 4 }));
+    }
+
+    #[test]
+    fn is_recover_secret_solve() {
+        let new_secret = recover_secret(2, "ab b c cdacd", vec![3, 3, 3, 3]);
+        let output = new_secret.solve();
+        assert_eq!(output.secret_sentence, "ab cd");
+        assert!(new_secret.verify(&output));
+    }
+
+    #[test]
+    fn is_recover_secret_verify() {
+        let new_secret = recover_secret(2, "ab b c cdacd", vec![3, 3, 3, 3]);
+        assert!(new_secret.verify(&RecoverSecretOutput { secret_sentence: "ab cd".to_string() }));
+        assert!(new_secret.verify(&RecoverSecretOutput { secret_sentence: "xab cdy".to_string() }));
+        assert!(!new_secret.verify(&RecoverSecretOutput { secret_sentence: "ba cd".to_string() }));
+        assert!(!new_secret.verify(&RecoverSecretOutput { secret_sentence: "abcd".to_string() }));
+    }
+
+    #[test]
+    fn is_recover_secret_solve_repeated_letters() {
+        let new_secret = recover_secret(1, "abba", vec![2, 2]);
+        let output = new_secret.solve();
+        assert_eq!(output.secret_sentence.len(), 3);
+        assert!(new_secret.verify(&output));
+
+        let new_secret = recover_secret(2, "hellhlolo wworoldelwe wd", vec![4, 3, 4, 3, 3, 3, 4]);
+        let output = new_secret.solve();
+        assert!(new_secret.verify(&RecoverSecretOutput { secret_sentence: "hello world".to_string() }));
+        assert!(output.secret_sentence.len() <= "hello world".len());
+        assert!(new_secret.verify(&output));
+    }
+
+    #[test]
+    fn is_recover_secret_solve_long_tuple() {
+        let letters = "ab".repeat(150);
+        let new_secret = recover_secret(1, &letters, vec![letters.len()]);
+        assert_eq!(new_secret.solve().secret_sentence, letters);
+    }
+
+    #[test]
+    fn is_recover_secret_solve_adds_missing_words() {
+        let new_secret = recover_secret(2, "ab", vec![2]);
+        assert_eq!(new_secret.solve().secret_sentence, "a b");
+        assert_eq!(new_secret.shortest_candidates(10, &SolveLimits::default(), &mut |_| {}).unwrap(), vec!["a b"]);
+    }
+
+    #[test]
+    fn is_recover_secret_shortest_candidates() {
+        let new_secret = recover_secret(1, "abba", vec![2, 2]);
+        assert_eq!(new_secret.shortest_candidates(10, &SolveLimits::default(), &mut |_| {}).unwrap(), vec!["aba", "bab"]);
+        assert_eq!(new_secret.shortest_candidates(1, &SolveLimits::default(), &mut |_| {}).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn is_recover_secret_greedy_candidate() {
+        let new_secret = recover_secret(3, "hellhlolo wworoldelwe wd", vec![4, 3, 4, 3, 3, 3, 4]);
+        let output = RecoverSecretOutput { secret_sentence: new_secret.greedy_candidate() };
+        assert!(new_secret.verify(&output));
+    }
+
+    #[test]
+    fn is_recover_secret_solve_within_deadline() {
+        let new_secret = recover_secret(3, "hellhlolo wworoldelwe wd", vec![4, 3, 4, 3, 3, 3, 4]);
+        let mut explored = 0;
+        let solved = new_secret.solve_within(&SolveLimits::default(), &mut |progress| if let Progress::ExploredStates(states) = progress { explored = states });
+        assert_eq!(solved, Solved::Answer(new_secret.solve()));
+        assert!(explored > 0);
+
+        let solved = new_secret.solve_within(&SolveLimits::default().with_timeout(Duration::ZERO), &mut |_| {});
+        let greedy = RecoverSecretOutput { secret_sentence: new_secret.greedy_candidate() };
+        assert!(new_secret.verify(&greedy));
+        assert_eq!(solved, Solved::BestSoFar { answer: greedy, reason: StopReason::Deadline });
+    }
+
+    #[test]
+    fn is_recover_secret_ranked_by_dictionary() {
+        let new_secret = recover_secret(2, "ab cd", vec![2, 3]);
+        assert!(new_secret.shortest_candidates(10, &SolveLimits::default(), &mut |_| {}).unwrap().len() > 1);
+        assert_eq!(new_secret.solve().secret_sentence, "a bcd");
+
+        let dictionary = Arc::new(Dictionary::from_words(["ab", "cd"]));
+        let new_secret = recover_secret(2, "ab cd", vec![2, 3]).with_dictionary(dictionary.clone());
+        assert_eq!(new_secret.solve().secret_sentence, "ab cd");
+
+        let unknown_words = recover_secret(2, "ef gh", vec![2, 3]);
+        let fallback = unknown_words.solve();
+        let unknown_words = recover_secret(2, "ef gh", vec![2, 3]).with_dictionary(dictionary);
+        assert_eq!(unknown_words.solve().secret_sentence, fallback.secret_sentence);
+        assert!(unknown_words.verify(&fallback));
+    }
+}
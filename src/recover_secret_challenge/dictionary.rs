@@ -0,0 +1,101 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory holding one `<language>.txt` word list per language
+pub const DEFAULT_DICTIONARY_DIRECTORY: &str = "dictionaries";
+
+/// Known words used to pick the most plausible secret sentence
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words = words.into_iter()
+            .map(|word| word.as_ref().trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        Dictionary { words }
+    }
+
+    /// Loads a word list with one word per line
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Dictionary::from_words(fs::read_to_string(path)?.lines()))
+    }
+
+    /// `DEFAULT_DICTIONARY_DIRECTORY` of the working directory when there is one, else the
+    /// word lists shipped with the crate
+    pub fn default_directory() -> PathBuf {
+        let local = PathBuf::from(DEFAULT_DICTIONARY_DIRECTORY);
+        if local.is_dir() {
+            local
+        } else {
+            Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_DICTIONARY_DIRECTORY)
+        }
+    }
+
+    pub fn path_for_language(directory: &Path, language: &str) -> PathBuf {
+        directory.join(format!("{language}.txt"))
+    }
+
+    pub fn for_language(directory: &Path, language: &str) -> io::Result<Self> {
+        Dictionary::load(&Dictionary::path_for_language(directory, language))
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    /// Number of words of the sentence found in the dictionary
+    pub fn score(&self, sentence: &str) -> usize {
+        sentence.split_whitespace().filter(|word| self.contains(word)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests_dictionary {
+    use super::*;
+
+    #[test]
+    fn is_dictionary_score() {
+        let dictionary = Dictionary::from_words(["Hello", " world ", ""]);
+        assert_eq!(dictionary.len(), 2);
+        assert!(dictionary.contains("HELLO"));
+        assert_eq!(dictionary.score("hello world"), 2);
+        assert_eq!(dictionary.score("hello wrold"), 1);
+        assert_eq!(dictionary.score("ehllo wrold"), 0);
+    }
+
+    #[test]
+    fn is_dictionary_shipped() {
+        let dictionary = Dictionary::for_language(&Dictionary::default_directory(), "en").unwrap();
+        assert!(dictionary.contains("hello") && dictionary.contains("world"));
+    }
+
+    #[test]
+    fn is_dictionary_for_language() {
+        let directory = std::env::temp_dir().join(format!("dictionaries-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(Dictionary::path_for_language(&directory, "fr"), "bonjour\nmonde\n").unwrap();
+
+        let dictionary = Dictionary::for_language(&directory, "fr").unwrap();
+        assert_eq!(dictionary.score("bonjour le monde"), 2);
+        assert!(Dictionary::for_language(&directory, "xx").is_err());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
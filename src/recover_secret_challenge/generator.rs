@@ -0,0 +1,102 @@
+use std::ops::RangeInclusive;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use super::{RecoverSecret, RecoverSecretInput};
+use crate::challenge_trait::{ChallengeGenerator, ChallengeTrait};
+use crate::solve_control::SolveLimits;
+
+#[derive(Debug, Clone)]
+pub struct RecoverSecretSettings {
+    pub word_count: RangeInclusive<usize>,
+    pub word_length: RangeInclusive<usize>,
+    /// Letters per tuple, grown when tuples of this size would leave several shortest sentences
+    pub tuple_size: RangeInclusive<usize>,
+}
+
+impl Default for RecoverSecretSettings {
+    fn default() -> Self {
+        RecoverSecretSettings { word_count: 2..=4, word_length: 2..=6, tuple_size: 3..=4 }
+    }
+}
+
+impl ChallengeGenerator for RecoverSecret {
+    type Settings = RecoverSecretSettings;
+
+    /// Random lowercase words cut into overlapping tuples, so that every pair of neighbouring
+    /// characters shows up in some tuple. Tuples get longer until the sentence is the only
+    /// shortest one matching them, which the whole sentence as a single tuple always is.
+    fn generate<R: Rng + ?Sized>(settings: &Self::Settings, rng: &mut R) -> Self::Input {
+        let word_count = rng.gen_range(settings.word_count.clone()).max(1);
+        let words: Vec<String> = (0..word_count).map(|_| {
+            let length = rng.gen_range(settings.word_length.clone()).max(1);
+            (0..length).map(|_| char::from(rng.gen_range(b'a'..=b'z'))).collect()
+        }).collect();
+        let sentence = words.join(" ");
+        let length = sentence.chars().count();
+
+        let mut tuple_size = (*settings.tuple_size.start()).max(2)..=(*settings.tuple_size.end()).max(2);
+        loop {
+            let input = cut(&sentence, word_count, tuple_size.clone(), rng);
+            if *tuple_size.start() >= length || is_determined(&input, &sentence) {
+                return input;
+            }
+            tuple_size = tuple_size.start() * 2..=tuple_size.end() * 2;
+        }
+    }
+}
+
+/// Shuffled windows over `sentence`, each one starting on the last character of another
+fn cut<R: Rng + ?Sized>(sentence: &str, word_count: usize, tuple_size: RangeInclusive<usize>, rng: &mut R) -> RecoverSecretInput {
+    let characters: Vec<char> = sentence.chars().collect();
+    let mut tuples: Vec<&[char]> = vec![];
+    let mut start = 0;
+    loop {
+        let end = (start + rng.gen_range(tuple_size.clone())).min(characters.len());
+        tuples.push(&characters[start..end]);
+        if end == characters.len() {
+            break;
+        }
+        start = end - 1;
+    }
+    tuples.shuffle(rng);
+    RecoverSecretInput {
+        word_count,
+        letters: tuples.iter().flat_map(|tuple| tuple.iter()).collect(),
+        tuple_sizes: tuples.iter().map(|tuple| tuple.len()).collect(),
+    }
+}
+
+fn is_determined(input: &RecoverSecretInput, sentence: &str) -> bool {
+    let Ok(recover_secret) = RecoverSecret::new(input.clone()) else { return false };
+    recover_secret.shortest_candidates(2, &SolveLimits::default(), &mut |_| {}).is_ok_and(|candidates| candidates == [sentence])
+}
+
+#[cfg(test)]
+mod tests_recover_secret_generator {
+    use super::*;
+
+    #[test]
+    fn is_recover_secret_generated_determined() {
+        let settings = RecoverSecretSettings::default();
+        for seed in 0..30 {
+            let input = RecoverSecret::generate_seeded(&settings, seed);
+            assert!(settings.word_count.contains(&input.word_count));
+            let recover_secret = RecoverSecret::new(input.clone()).unwrap();
+            let candidates = recover_secret.shortest_candidates(2, &SolveLimits::default(), &mut |_| {}).unwrap();
+            assert_eq!(candidates.len(), 1, "seed {seed}: {input:?}");
+            assert!(recover_secret.verify(&recover_secret.solve()));
+        }
+    }
+
+    #[test]
+    fn is_recover_secret_generated_seeded() {
+        let settings = RecoverSecretSettings { word_count: 3..=3, word_length: 4..=4, tuple_size: 3..=3 };
+        let input = RecoverSecret::generate_seeded(&settings, 5);
+        assert_eq!(input.word_count, 3);
+        assert_eq!(input.letters, RecoverSecret::generate_seeded(&settings, 5).letters);
+        assert_eq!(input.tuple_sizes.iter().sum::<usize>(), input.letters.chars().count());
+        assert!(input.tuple_sizes.iter().all(|&size| size >= 2));
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Shared flag asking a running solver to stop, cloned into whoever may cancel it
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// When a solver has to give up: a deadline, a cancellation, or both
+#[derive(Debug, Clone, Default)]
+pub struct SolveLimits {
+    pub deadline: Option<Instant>,
+    pub cancellation: CancellationToken,
+}
+
+impl SolveLimits {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Why the solver should stop now, `None` while it may go on
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        if self.cancellation.is_cancelled() {
+            Some(StopReason::Cancelled)
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(StopReason::Deadline)
+        } else {
+            None
+        }
+    }
+
+    pub fn should_stop(&self) -> bool {
+        self.stop_reason().is_some()
+    }
+}
+
+/// Work done so far, reported to the progress callback of `solve_within`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// Candidates tested one by one, e.g. HashCash seeds
+    Attempts(u64),
+    /// States reached by a search, e.g. maze positions or sentence prefixes
+    ExploredStates(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Deadline,
+    Cancelled,
+    /// Everything was searched without finding an answer
+    Exhausted,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Deadline => write!(f, "deadline reached"),
+            StopReason::Cancelled => write!(f, "cancelled"),
+            StopReason::Exhausted => write!(f, "no answer exists"),
+        }
+    }
+}
+
+/// Outcome of `solve_within`
+#[derive(Debug, PartialEq)]
+pub enum Solved<O> {
+    Answer(O),
+    /// Stopped early: `answer` is valid but may not be the best one
+    BestSoFar { answer: O, reason: StopReason },
+    GaveUp(StopReason),
+}
+
+impl<O> Solved<O> {
+    /// The answer to send, if any was found
+    pub fn answer(self) -> Option<O> {
+        match self {
+            Solved::Answer(answer) | Solved::BestSoFar { answer, .. } => Some(answer),
+            Solved::GaveUp(_) => None,
+        }
+    }
+
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        match self {
+            Solved::Answer(_) => None,
+            Solved::BestSoFar { reason, .. } | Solved::GaveUp(reason) => Some(*reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_solve_control {
+    use super::*;
+
+    #[test]
+    fn is_solve_limits_stop_reason() {
+        assert_eq!(SolveLimits::default().stop_reason(), None);
+        assert_eq!(SolveLimits::default().with_timeout(Duration::ZERO).stop_reason(), Some(StopReason::Deadline));
+        assert_eq!(SolveLimits::default().with_timeout(Duration::from_secs(60)).stop_reason(), None);
+
+        let cancellation = CancellationToken::new();
+        let limits = SolveLimits::default().with_timeout(Duration::ZERO).with_cancellation(cancellation.clone());
+        cancellation.cancel();
+        assert_eq!(limits.stop_reason(), Some(StopReason::Cancelled));
+    }
+
+    #[test]
+    fn is_solved_answer() {
+        assert_eq!(Solved::Answer(1).answer(), Some(1));
+        assert_eq!(Solved::BestSoFar { answer: 2, reason: StopReason::Deadline }.stop_reason(), Some(StopReason::Deadline));
+        assert_eq!(Solved::<u8>::GaveUp(StopReason::Cancelled).answer(), None);
+    }
+}
@@ -0,0 +1,209 @@
+use std::cmp::Ordering;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::game_state::GameState;
+use crate::protocol::PublicPlayer;
+
+/// Names of the built-in strategies, as given to `strategy_named`
+pub const STRATEGY_NAMES: [&str; 4] = ["leader", "closest-above", "slowest", "random"];
+
+/// Picks the player a solved challenge is passed on to
+pub trait TargetStrategy {
+    /// Name of the chosen player among `players`, entries of the leader board of `state`,
+    /// `None` when nobody fits
+    fn choose_among(&mut self, state: &GameState, players: &[&PublicPlayer]) -> Option<String>;
+
+    /// Name of the chosen player among the leader board of `state`, `None` when nobody fits
+    fn choose(&mut self, state: &GameState) -> Option<String> {
+        let players: Vec<&PublicPlayer> = state.leader_board().0.iter().collect();
+        self.choose_among(state, &players)
+    }
+}
+
+fn others<'a>(state: &'a GameState, players: &'a [&PublicPlayer]) -> impl DoubleEndedIterator<Item = &'a PublicPlayer> {
+    let me = state.my_name();
+    players.iter().copied().filter(move |player| Some(player.name.as_str()) != me)
+}
+
+/// The player with the best score but us
+#[derive(Debug, Default)]
+pub struct Leader;
+
+impl TargetStrategy for Leader {
+    fn choose_among(&mut self, state: &GameState, players: &[&PublicPlayer]) -> Option<String> {
+        // `max_by_key` keeps the last of equal players, `rev` makes it the first one of the board
+        others(state, players).rev().max_by_key(|player| player.score).map(|player| player.name.clone())
+    }
+}
+
+/// The player with the lowest score above ours, or the best of the others when we lead
+#[derive(Debug, Default)]
+pub struct ClosestAbove;
+
+impl TargetStrategy for ClosestAbove {
+    fn choose_among(&mut self, state: &GameState, players: &[&PublicPlayer]) -> Option<String> {
+        let my_score = state.me().map_or(i32::MIN, |player| player.score);
+        others(state, players)
+            .filter(|player| player.score > my_score)
+            .min_by_key(|player| player.score)
+            .map(|player| player.name.clone())
+            .or_else(|| Leader.choose_among(state, players))
+    }
+}
+
+/// The player who spent the most time on its challenges so far
+#[derive(Debug, Default)]
+pub struct Slowest;
+
+impl TargetStrategy for Slowest {
+    fn choose_among(&mut self, state: &GameState, players: &[&PublicPlayer]) -> Option<String> {
+        others(state, players)
+            .rev()
+            .max_by(|player, other| player.total_used_time.partial_cmp(&other.total_used_time).unwrap_or(Ordering::Equal))
+            .map(|player| player.name.clone())
+    }
+}
+
+/// Any active player but us
+#[derive(Debug)]
+pub struct RandomActive {
+    rng: StdRng,
+}
+
+impl RandomActive {
+    pub fn new() -> Self {
+        RandomActive { rng: StdRng::from_entropy() }
+    }
+
+    pub fn seeded(seed: u64) -> Self {
+        RandomActive { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Default for RandomActive {
+    fn default() -> Self {
+        RandomActive::new()
+    }
+}
+
+impl TargetStrategy for RandomActive {
+    fn choose_among(&mut self, state: &GameState, players: &[&PublicPlayer]) -> Option<String> {
+        let active: Vec<&PublicPlayer> = others(state, players).filter(|player| player.is_active).collect();
+        active.choose(&mut self.rng).map(|player| player.name.clone())
+    }
+}
+
+/// Hides the inactive players from `strategy` and never lets it pick us, so that a challenge
+/// is only passed to someone who can answer it
+#[derive(Debug, Default)]
+pub struct Eligible<S> {
+    strategy: S,
+}
+
+impl<S: TargetStrategy> Eligible<S> {
+    pub fn new(strategy: S) -> Self {
+        Eligible { strategy }
+    }
+}
+
+impl<S: TargetStrategy> TargetStrategy for Eligible<S> {
+    fn choose_among(&mut self, state: &GameState, players: &[&PublicPlayer]) -> Option<String> {
+        // Our own entry stays visible to strategies comparing us with the others
+        let me = state.my_name();
+        let visible: Vec<&PublicPlayer> = players.iter().copied().filter(|player| player.is_active || Some(player.name.as_str()) == me).collect();
+        self.strategy
+            .choose_among(state, &visible)
+            .filter(|name| Some(name.as_str()) != me && visible.iter().any(|player| &player.name == name))
+    }
+}
+
+/// Built-in strategy called `name`, restricted to the eligible players
+pub fn strategy_named(name: &str) -> Option<Box<dyn TargetStrategy>> {
+    let strategy: Box<dyn TargetStrategy> = match name {
+        "leader" => Box::new(Eligible::new(Leader)),
+        "closest-above" => Box::new(Eligible::new(ClosestAbove)),
+        "slowest" => Box::new(Eligible::new(Slowest)),
+        "random" => Box::new(Eligible::new(RandomActive::new())),
+        _ => return None,
+    };
+    Some(strategy)
+}
+
+#[cfg(test)]
+mod tests_target_strategy {
+    use super::*;
+    use crate::protocol::{Message, PublicLeaderBoard, Subscribe, SubscribeResult};
+
+    fn player(name: &str, score: i32, is_active: bool, total_used_time: f64) -> PublicPlayer {
+        PublicPlayer { name: name.to_string(), stream_id: String::new(), score, steps: 0, is_active, total_used_time }
+    }
+
+    fn state(board: PublicLeaderBoard, me: &str) -> GameState {
+        let mut state = GameState::new();
+        state.observe(&Message::Subscribe(Subscribe { name: me.to_string() }));
+        state.observe(&Message::SubscribeResult(SubscribeResult::Ok));
+        state.observe(&Message::PublicLeaderBoard(board));
+        state
+    }
+
+    fn board() -> PublicLeaderBoard {
+        PublicLeaderBoard(vec![
+            player("me", 3, true, 1.0),
+            player("ann", 9, true, 0.5),
+            player("bob", 5, true, 4.0),
+            player("cid", 12, false, 9.0),
+            player("dan", 1, true, 2.0),
+        ])
+    }
+
+    #[test]
+    fn is_target_strategy_eligible() {
+        assert_eq!(Eligible::new(Leader).choose(&state(board(), "me")), Some("ann".to_string()));
+        assert_eq!(Eligible::new(ClosestAbove).choose(&state(board(), "me")), Some("bob".to_string()));
+        assert_eq!(Eligible::new(Slowest).choose(&state(board(), "me")), Some("bob".to_string()));
+        assert_eq!(Eligible::new(ClosestAbove).choose(&state(board(), "ann")), Some("bob".to_string()));
+
+        let mut random = Eligible::new(RandomActive::seeded(1));
+        for _ in 0..20 {
+            let name = random.choose(&state(board(), "me")).unwrap();
+            assert!(["ann", "bob", "dan"].contains(&name.as_str()), "{name}");
+        }
+    }
+
+    #[test]
+    fn is_target_strategy_unfiltered() {
+        assert_eq!(Leader.choose(&state(board(), "me")), Some("cid".to_string()));
+        assert_eq!(Leader.choose(&state(board(), "cid")), Some("ann".to_string()));
+        assert_eq!(Slowest.choose(&state(board(), "me")), Some("cid".to_string()));
+        assert_eq!(ClosestAbove.choose(&state(board(), "ann")), Some("cid".to_string()));
+        assert_eq!(ClosestAbove.choose(&state(board(), "cid")), Some("ann".to_string()));
+
+        let state = state(board(), "me");
+        let board = state.leader_board();
+        assert_eq!(Leader.choose_among(&state, &[&board.0[2], &board.0[4]]), Some("bob".to_string()));
+        assert_eq!(ClosestAbove.choose_among(&state, &[&board.0[0], &board.0[4]]), Some("dan".to_string()));
+    }
+
+    #[test]
+    fn is_target_strategy_without_candidates() {
+        let empty = PublicLeaderBoard(vec![]);
+        let alone = PublicLeaderBoard(vec![player("me", 0, true, 0.0), player("gone", 4, false, 0.0)]);
+        for name in STRATEGY_NAMES {
+            let mut strategy = strategy_named(name).unwrap();
+            assert_eq!(strategy.choose(&state(empty.clone(), "me")), None, "{name}");
+            assert_eq!(strategy.choose(&state(alone.clone(), "me")), None, "{name}");
+        }
+        assert!(strategy_named("worst").is_none());
+    }
+
+    #[test]
+    fn is_target_strategy_ties_first() {
+        let tied = state(PublicLeaderBoard(vec![player("me", 1, true, 1.0), player("ann", 2, true, 1.0), player("bob", 2, true, 1.0)]), "me");
+        assert_eq!(Eligible::new(Leader).choose(&tied), Some("ann".to_string()));
+        assert_eq!(Eligible::new(ClosestAbove).choose(&tied), Some("ann".to_string()));
+        assert_eq!(Eligible::new(Slowest).choose(&tied), Some("ann".to_string()));
+    }
+}